@@ -1,42 +1,50 @@
-use std::{
-    collections::HashMap,
-    error::Error,
-    sync::{atomic::AtomicU8, Arc},
-};
+use std::{collections::HashMap, future::Future, sync::Arc, time::Duration};
 
 use iced::{
     futures::{channel::mpsc::Sender, SinkExt, StreamExt},
-    widget::{column, row},
+    widget::{column, row, text},
     Element, Task,
 };
+use re_set_lib::LOG;
 use zbus::{Connection, Proxy};
 
 use crate::{
     components::{
         audio_card::{device_card_view, populate_audio_cards},
         comborow::{ComboPickerTitle, CustomPickList, PickerVariant},
+        equalizer_card::equalizer_view,
+        error_banner::error_banner,
         select_row::picklist_to_row,
+        toast::{toast_view, Toast},
     },
-    utils::ignore,
-    PageId, ReSetMessage,
+    utils::{daemon_error_message, ignore},
+    ReSetMessage,
 };
 
-use super::dbus_interface::{
-    AudioCard, AudioDbusProxy, AudioSink, AudioSource, InputStream, OutputStream, TIndex,
+use super::{
+    dbus_interface::{
+        AudioCard, AudioDbusProxy, AudioEqualizer, AudioSink, AudioSource, InputStream,
+        OutputStream, TIndex,
+    },
+    equalizer::{find_preset, EqualizerQueue, EqualizerQueueState, CUSTOM_PRESET},
+    history::{AudioHistory, AudioObjectKind, HistoryEntry},
+    speaker_test::{channel_layout, AudioChannel, SpeakerTest, TEST_SOUND_DURATION},
+    volume_queue::{VolumeQueue, VolumeQueueState},
 };
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub enum AudioVariant {
     Input,
     #[default]
     Output,
     Cards,
     Devices,
+    Equalizer,
     InputAndOutput,
 }
 
-pub struct AudioModel<'a> {
-    audio_proxy: Arc<AudioDbusProxy<'a>>,
+pub struct AudioModel {
+    audio_proxy: Arc<AudioDbusProxy<'static>>,
     default_sink: u32,
     // TODO beforepr find a better way to handle broken defaults
     default_sink_dummy: bool,
@@ -48,9 +56,17 @@ pub struct AudioModel<'a> {
     input_streams: HashMap<u32, InputStream>,
     output_streams: HashMap<u32, OutputStream>,
     cards: HashMap<u32, AudioCard>,
+    speaker_test: Option<SpeakerTest>,
+    equalizers: HashMap<u32, AudioEqualizer>,
+    history: AudioHistory,
+    toast: Option<Toast>,
+    toast_count: u32,
+    volume_queue: VolumeQueue,
+    equalizer_queue: EqualizerQueue,
+    error: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum AudioMsg {
     SetAudioVariant(AudioVariant),
     SetSinkVolume(u32, u16, u32),
@@ -76,17 +92,66 @@ pub enum AudioMsg {
     AddAudioCard(AudioCard),
     RemoveAudioCard(u32),
     SetProfileOfCard(u32, String),
+    TestSinkChannel(u32, AudioChannel),
+    TestAllSinkChannels(u32),
+    SinkChannelTestDone(u32, AudioChannel),
+    StopSinkTest,
+    SetEqualizerEnabled(u32, bool),
+    SetEqualizerBand(u32, usize, f64),
+    SetEqualizerPreset(u32, String),
+    ReceiveEqualizer(u32, AudioEqualizer),
+    EqualizerSent(u32, Result<AudioEqualizer, String>),
+    VolumeSent(AudioObjectKind, u32, Result<u32, String>),
+    /// Volume of every channel, used to undo a volume change
+    RestoreVolume(AudioObjectKind, u32, u16, Vec<u32>),
+    /// A daemon call failed, the optional rollback reverts the local change
+    CallFailed(String, Option<Box<Rollback>>),
+    DismissError,
+    Undo,
+    Redo,
+    DismissToast(u32),
+    /// The audio page became visible
+    EnterPage,
+    /// The audio page was left
+    LeavePage,
 }
 
-async fn create_audio_proxy(ctx: &Connection) -> Result<AudioDbusProxy<'static>, Box<dyn Error>> {
-    let proxy = AudioDbusProxy::new(ctx).await?;
-    Ok(proxy)
+/// Reverts a change locally if the daemon rejects it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rollback {
+    pub msg: AudioMsg,
+    /// History entry of the change, dropped together with it
+    pub entry: Option<u32>,
 }
 
+/// How long the undo toast is shown after a change
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
 fn wrap(audio_msg: AudioMsg) -> ReSetMessage {
     ReSetMessage::SubMsgAudio(audio_msg)
 }
 
+/// Runs a daemon call without waiting for it in the UI,
+/// failures are reported back together with the change that reverts the local state
+fn dbus_call<T>(
+    call: impl Future<Output = Result<T, zbus::Error>> + Send + 'static,
+    rollback: Option<Rollback>,
+) -> Task<ReSetMessage>
+where
+    T: Send + 'static,
+{
+    Task::future(async move {
+        match call.await {
+            Ok(_) => None,
+            Err(error) => Some(wrap(AudioMsg::CallFailed(
+                daemon_error_message(error),
+                rollback.map(Box::new),
+            ))),
+        }
+    })
+    .and_then(Task::done)
+}
+
 fn to_map<T>(elements: Vec<T>) -> HashMap<u32, T>
 where
     T: TIndex,
@@ -102,58 +167,17 @@ where
 pub async fn watch_audio_dbus_signals(
     sender: &mut Sender<ReSetMessage>,
     conn: Arc<Connection>,
-    current_page_id: Arc<AtomicU8>,
 ) -> Result<(), zbus::Error> {
-    let proxy = AudioDbusProxy::new(&conn).await.expect("no proxy");
+    let proxy = AudioDbusProxy::new(&conn).await?;
     let mut signals = Proxy::receive_all_signals(&proxy.into_inner()).await?;
-    loop {
-        if current_page_id.load(std::sync::atomic::Ordering::SeqCst) != PageId::Audio.into() {
-            break;
-        }
-        if let Some(msg) = signals.next().await {
-            match msg.header().member().unwrap().to_string().as_str() {
-                "OutputStreamAdded" | "OutputStreamChanged" => {
-                    let obj: OutputStream = msg.body().deserialize()?;
-                    let _res = sender.send(wrap(AudioMsg::AddOutputStream(obj))).await;
-                }
-                "OutputStreamRemoved" => {
-                    let obj: u32 = msg.body().deserialize()?;
-                    let _res = sender.send(wrap(AudioMsg::RemoveOutputStream(obj))).await;
-                }
-                "InputStreamAdded" | "InputStreamChanged" => {
-                    let obj: InputStream = msg.body().deserialize()?;
-                    let _res = sender.send(wrap(AudioMsg::AddInputStream(obj))).await;
-                }
-                "InputStreamRemoved" => {
-                    let obj: u32 = msg.body().deserialize()?;
-                    let _res = sender.send(wrap(AudioMsg::RemoveInputStream(obj))).await;
-                }
-                "SinkAdded" | "SinkChanged" => {
-                    let obj: AudioSink = msg.body().deserialize()?;
-                    let _res = sender.send(wrap(AudioMsg::AddSink(obj))).await;
-                }
-                "SinkRemoved" => {
-                    let obj: u32 = msg.body().deserialize()?;
-                    let _res = sender.send(wrap(AudioMsg::RemoveSink(obj))).await;
-                }
-                "SourceAdded" | "SourceChanged" => {
-                    let obj: AudioSource = msg.body().deserialize()?;
-                    let _res = sender.send(wrap(AudioMsg::AddSource(obj))).await;
-                }
-                "SourceRemoved" => {
-                    let obj: u32 = msg.body().deserialize()?;
-                    let _res = sender.send(wrap(AudioMsg::RemoveSource(obj))).await;
-                }
-                "CardAdded" | "CardChanged" => {
-                    let obj: AudioCard = msg.body().deserialize()?;
-                    let _res = sender.send(wrap(AudioMsg::AddAudioCard(obj))).await;
-                }
-                "CardRemoved" => {
-                    let obj: u32 = msg.body().deserialize()?;
-                    let _res = sender.send(wrap(AudioMsg::RemoveAudioCard(obj))).await;
-                }
-                _ => (),
+    while let Some(msg) = signals.next().await {
+        match audio_signal(&msg) {
+            Ok(Some(audio_msg)) => {
+                let _res = sender.send(wrap(audio_msg)).await;
             }
+            Ok(None) => (),
+            // a malformed signal does not end the live updates
+            Err(_) => LOG!("Skipped an audio signal that could not be read"),
         }
     }
 
@@ -161,13 +185,57 @@ pub async fn watch_audio_dbus_signals(
     Ok(())
 }
 
-impl AudioModel<'_> {
+/// Reads a signal of the daemon, None for signals that are not handled
+fn audio_signal(msg: &zbus::Message) -> Result<Option<AudioMsg>, zbus::Error> {
+    let audio_msg = match msg.header().member().unwrap().to_string().as_str() {
+        "OutputStreamAdded" | "OutputStreamChanged" => {
+            let obj: OutputStream = msg.body().deserialize()?;
+            Some(AudioMsg::AddOutputStream(obj))
+        }
+        "OutputStreamRemoved" => {
+            let obj: u32 = msg.body().deserialize()?;
+            Some(AudioMsg::RemoveOutputStream(obj))
+        }
+        "InputStreamAdded" | "InputStreamChanged" => {
+            let obj: InputStream = msg.body().deserialize()?;
+            Some(AudioMsg::AddInputStream(obj))
+        }
+        "InputStreamRemoved" => {
+            let obj: u32 = msg.body().deserialize()?;
+            Some(AudioMsg::RemoveInputStream(obj))
+        }
+        "SinkAdded" | "SinkChanged" => {
+            let obj: AudioSink = msg.body().deserialize()?;
+            Some(AudioMsg::AddSink(obj))
+        }
+        "SinkRemoved" => {
+            let obj: u32 = msg.body().deserialize()?;
+            Some(AudioMsg::RemoveSink(obj))
+        }
+        "SourceAdded" | "SourceChanged" => {
+            let obj: AudioSource = msg.body().deserialize()?;
+            Some(AudioMsg::AddSource(obj))
+        }
+        "SourceRemoved" => {
+            let obj: u32 = msg.body().deserialize()?;
+            Some(AudioMsg::RemoveSource(obj))
+        }
+        "CardAdded" | "CardChanged" => {
+            let obj: AudioCard = msg.body().deserialize()?;
+            Some(AudioMsg::AddAudioCard(obj))
+        }
+        "CardRemoved" => {
+            let obj: u32 = msg.body().deserialize()?;
+            Some(AudioMsg::RemoveAudioCard(obj))
+        }
+        _ => None,
+    };
+    Ok(audio_msg)
+}
+
+impl AudioModel {
     pub async fn new(ctx: &Connection) -> Result<Self, zbus::Error> {
-        let proxy = Arc::new(
-            create_audio_proxy(ctx)
-                .await
-                .expect("Could not create proxy for audio"),
-        ); // TODO beforepr expect
+        let proxy = Arc::new(AudioDbusProxy::new(ctx).await?);
         let sinks = to_map(proxy.list_sinks().await?);
         let default_sink = proxy.get_default_sink().await?;
         let input_streams = to_map(proxy.list_input_streams().await?);
@@ -187,36 +255,301 @@ impl AudioModel<'_> {
             cards,
             default_sink_dummy: false,
             default_source_dummy: false,
+            speaker_test: None,
+            equalizers: HashMap::new(),
+            history: Default::default(),
+            toast: None,
+            toast_count: 0,
+            volume_queue: Default::default(),
+            equalizer_queue: Default::default(),
+            error: None,
         })
     }
 
-    pub async fn update(&mut self, msg: AudioMsg) -> Option<Task<ReSetMessage>> {
+    pub fn update(&mut self, msg: AudioMsg) -> Option<Task<ReSetMessage>> {
+        match msg {
+            AudioMsg::Undo => {
+                let entry = self.history.undo()?;
+                let id = entry.id();
+                let rollback = Rollback {
+                    msg: entry.redo.clone(),
+                    entry: Some(id),
+                };
+                match self.apply(entry.undo, Some(rollback)) {
+                    Some(task) => {
+                        let toast = self.show_toast(
+                            format!("Undone: {}", entry.description),
+                            Some(("Redo", wrap(AudioMsg::Redo))),
+                        );
+                        Some(Task::batch([task, toast]))
+                    }
+                    None => {
+                        // the device or stream is gone
+                        self.history.discard(id);
+                        Some(
+                            self.show_toast(format!("Could not undo: {}", entry.description), None),
+                        )
+                    }
+                }
+            }
+            AudioMsg::Redo => {
+                let entry = self.history.redo()?;
+                let id = entry.id();
+                let rollback = Rollback {
+                    msg: entry.undo.clone(),
+                    entry: Some(id),
+                };
+                match self.apply(entry.redo, Some(rollback)) {
+                    Some(task) => {
+                        let toast = self.show_toast(
+                            format!("Redone: {}", entry.description),
+                            Some(("Undo", wrap(AudioMsg::Undo))),
+                        );
+                        Some(Task::batch([task, toast]))
+                    }
+                    None => {
+                        self.history.discard(id);
+                        Some(
+                            self.show_toast(format!("Could not redo: {}", entry.description), None),
+                        )
+                    }
+                }
+            }
+            AudioMsg::DismissToast(id) => {
+                if self.toast.as_ref().is_some_and(|toast| toast.id == id) {
+                    self.toast = None;
+                }
+                Some(Task::none())
+            }
+            msg => match self.history_entry(&msg) {
+                Some(entry) => {
+                    let description = entry.description.clone();
+                    let rollback = Rollback {
+                        msg: entry.undo.clone(),
+                        entry: Some(self.history.record(entry)),
+                    };
+                    let task = self.apply(msg, Some(rollback)).unwrap_or(Task::none());
+                    let toast = self.show_toast(description, Some(("Undo", wrap(AudioMsg::Undo))));
+                    Some(Task::batch([task, toast]))
+                }
+                None => self.apply(msg, None),
+            },
+        }
+    }
+
+    /// Creates the entry needed to revert a user initiated change,
+    /// must be called before the change is applied.
+    fn history_entry(&self, msg: &AudioMsg) -> Option<HistoryEntry> {
+        let redo = msg.clone();
+        let entry = match msg {
+            AudioMsg::SetSinkVolume(index, channels, _) => {
+                let sink = self.sinks.get(index)?;
+                HistoryEntry::new(
+                    format!("Volume of {}", sink.alias),
+                    AudioMsg::RestoreVolume(
+                        AudioObjectKind::Sink,
+                        *index,
+                        *channels,
+                        sink.volume.clone(),
+                    ),
+                    redo,
+                )
+                .merge_key(AudioObjectKind::Sink, *index)
+            }
+            AudioMsg::SetSinkMute(index, muted) => {
+                let sink = self.sinks.get(index)?;
+                HistoryEntry::new(
+                    mute_description(*muted, &sink.alias),
+                    AudioMsg::SetSinkMute(*index, sink.muted),
+                    redo,
+                )
+            }
+            AudioMsg::SetDefaultSink(index) => {
+                let sink = self.sinks.get(index)?;
+                if *index == self.default_sink || self.default_sink_dummy {
+                    return None;
+                }
+                HistoryEntry::new(
+                    format!("Default output set to {}", sink.alias),
+                    AudioMsg::SetDefaultSink(self.default_sink),
+                    redo,
+                )
+            }
+            AudioMsg::SetSourceVolume(index, channels, _) => {
+                let source = self.sources.get(index)?;
+                HistoryEntry::new(
+                    format!("Volume of {}", source.alias),
+                    AudioMsg::RestoreVolume(
+                        AudioObjectKind::Source,
+                        *index,
+                        *channels,
+                        source.volume.clone(),
+                    ),
+                    redo,
+                )
+                .merge_key(AudioObjectKind::Source, *index)
+            }
+            AudioMsg::SetSourceMute(index, muted) => {
+                let source = self.sources.get(index)?;
+                HistoryEntry::new(
+                    mute_description(*muted, &source.alias),
+                    AudioMsg::SetSourceMute(*index, source.muted),
+                    redo,
+                )
+            }
+            AudioMsg::SetDefaultSource(index) => {
+                let source = self.sources.get(index)?;
+                if *index == self.default_source || self.default_source_dummy {
+                    return None;
+                }
+                HistoryEntry::new(
+                    format!("Default input set to {}", source.alias),
+                    AudioMsg::SetDefaultSource(self.default_source),
+                    redo,
+                )
+            }
+            AudioMsg::SetInputStreamVolume(index, channels, _) => {
+                let stream = self.input_streams.get(index)?;
+                HistoryEntry::new(
+                    format!("Volume of {}", stream.application_name),
+                    AudioMsg::RestoreVolume(
+                        AudioObjectKind::InputStream,
+                        *index,
+                        *channels,
+                        stream.volume.clone(),
+                    ),
+                    redo,
+                )
+                .merge_key(AudioObjectKind::InputStream, *index)
+            }
+            AudioMsg::SetInputStreamMute(index, muted) => {
+                let stream = self.input_streams.get(index)?;
+                HistoryEntry::new(
+                    mute_description(*muted, &stream.application_name),
+                    AudioMsg::SetInputStreamMute(*index, stream.muted),
+                    redo,
+                )
+            }
+            AudioMsg::SetSinkOfInputStream(input_stream, sink) => {
+                let stream = self.input_streams.get(&input_stream.index)?;
+                let previous_sink = self.sinks.get(&stream.sink_index)?;
+                if previous_sink.index == sink.index {
+                    return None;
+                }
+                HistoryEntry::new(
+                    format!("Moved {} to {}", stream.application_name, sink.alias),
+                    AudioMsg::SetSinkOfInputStream(stream.clone(), previous_sink.clone()),
+                    redo,
+                )
+            }
+            AudioMsg::SetOutputStreamVolume(index, channels, _) => {
+                let stream = self.output_streams.get(index)?;
+                HistoryEntry::new(
+                    format!("Volume of {}", stream.application_name),
+                    AudioMsg::RestoreVolume(
+                        AudioObjectKind::OutputStream,
+                        *index,
+                        *channels,
+                        stream.volume.clone(),
+                    ),
+                    redo,
+                )
+                .merge_key(AudioObjectKind::OutputStream, *index)
+            }
+            AudioMsg::SetOutputStreamMute(index, muted) => {
+                let stream = self.output_streams.get(index)?;
+                HistoryEntry::new(
+                    mute_description(*muted, &stream.application_name),
+                    AudioMsg::SetOutputStreamMute(*index, stream.muted),
+                    redo,
+                )
+            }
+            AudioMsg::SetSourceOfOutputStream(output_stream, source) => {
+                let stream = self.output_streams.get(&output_stream.index)?;
+                let previous_source = self.sources.get(&stream.source_index)?;
+                if previous_source.index == source.index {
+                    return None;
+                }
+                HistoryEntry::new(
+                    format!("Moved {} to {}", stream.application_name, source.alias),
+                    AudioMsg::SetSourceOfOutputStream(stream.clone(), previous_source.clone()),
+                    redo,
+                )
+            }
+            AudioMsg::SetProfileOfCard(index, profile) => {
+                let card = self.cards.get(index)?;
+                if card.active_profile == *profile {
+                    return None;
+                }
+                HistoryEntry::new(
+                    format!("Profile of {} set to {}", card.name, profile),
+                    AudioMsg::SetProfileOfCard(*index, card.active_profile.clone()),
+                    redo,
+                )
+            }
+            _ => return None,
+        };
+        Some(entry)
+    }
+
+    fn show_toast(
+        &mut self,
+        text: String,
+        action: Option<(&'static str, ReSetMessage)>,
+    ) -> Task<ReSetMessage> {
+        self.toast_count = self.toast_count.wrapping_add(1);
+        let id = self.toast_count;
+        self.toast = Some(Toast { id, text, action });
+        Task::perform(async_io::Timer::after(TOAST_DURATION), move |_| {
+            wrap(AudioMsg::DismissToast(id))
+        })
+    }
+
+    /// Applies a change to the model right away, the daemon is updated in the returned task.
+    /// `rollback` is applied locally if the daemon rejects the change.
+    fn apply(&mut self, msg: AudioMsg, rollback: Option<Rollback>) -> Option<Task<ReSetMessage>> {
         let cmd = match msg {
             AudioMsg::SetAudioVariant(audio_variant) => {
+                let load_task = if matches!(audio_variant, AudioVariant::Equalizer) {
+                    self.load_equalizer(self.default_sink)
+                } else {
+                    Task::none()
+                };
                 self.audio_variant = audio_variant;
-                Task::done(ReSetMessage::SetPage(crate::PageId::Audio))
+                Task::batch([
+                    load_task,
+                    Task::done(ReSetMessage::SetPage(crate::PageId::Audio)),
+                ])
             }
             AudioMsg::SetSinkVolume(index, channels, volume) => {
                 let current_sink = self.sinks.get_mut(&index)?;
+                let current = current_sink.volume.first().copied().unwrap_or_default();
                 set_volume(&mut current_sink.volume, volume);
-                ignore(
-                    self.audio_proxy
-                        .set_sink_volume(index, channels, volume)
-                        .await,
-                );
-                Task::none()
+                let entry = rollback.and_then(|rollback| rollback.entry);
+                self.send_volume(
+                    AudioObjectKind::Sink,
+                    index,
+                    channels,
+                    volume,
+                    current,
+                    entry,
+                )
             }
             AudioMsg::SetSinkMute(index, muted) => {
                 // TODO beforepr handle unwrap
                 self.sinks.get_mut(&index)?.muted = muted;
-                ignore(self.audio_proxy.set_sink_mute(index, muted).await);
-                Task::none()
+                let proxy = self.audio_proxy.clone();
+                dbus_call(
+                    async move { proxy.set_sink_mute(index, muted).await },
+                    rollback,
+                )
             }
-            AudioMsg::AddSink(sink) => {
+            AudioMsg::AddSink(mut sink) => {
                 if self.default_sink_dummy {
                     self.default_sink = sink.index;
                     self.default_sink_dummy = false;
                 }
+                self.keep_pending_volume(AudioObjectKind::Sink, sink.index, &mut sink.volume);
                 ignore(self.sinks.insert(sink.index, sink));
                 Task::none()
             }
@@ -234,29 +567,45 @@ impl AudioModel<'_> {
             }
             AudioMsg::SetInputStreamMute(index, muted) => {
                 self.input_streams.get_mut(&index)?.muted = muted;
-                ignore(self.audio_proxy.set_input_stream_mute(index, muted).await);
-                Task::none()
+                let proxy = self.audio_proxy.clone();
+                dbus_call(
+                    async move { proxy.set_input_stream_mute(index, muted).await },
+                    rollback,
+                )
             }
             AudioMsg::SetInputStreamVolume(index, channels, volume) => {
                 let current_input_stream = self.input_streams.get_mut(&index)?;
+                let current = current_input_stream
+                    .volume
+                    .first()
+                    .copied()
+                    .unwrap_or_default();
                 set_volume(&mut current_input_stream.volume, volume);
-                ignore(
-                    self.audio_proxy
-                        .set_input_stream_volume(index, channels, volume)
-                        .await,
-                );
-                Task::none()
+                let entry = rollback.and_then(|rollback| rollback.entry);
+                self.send_volume(
+                    AudioObjectKind::InputStream,
+                    index,
+                    channels,
+                    volume,
+                    current,
+                    entry,
+                )
             }
             AudioMsg::SetSinkOfInputStream(input_stream, sink) => {
+                self.sinks.get(&sink.index)?;
                 self.input_streams.get_mut(&input_stream.index)?.sink_index = sink.index;
-                ignore(
-                    self.audio_proxy
-                        .set_sink_of_input_stream(input_stream, sink)
-                        .await,
-                );
-                Task::none()
+                let proxy = self.audio_proxy.clone();
+                dbus_call(
+                    async move { proxy.set_sink_of_input_stream(input_stream, sink).await },
+                    rollback,
+                )
             }
-            AudioMsg::AddInputStream(input_stream) => {
+            AudioMsg::AddInputStream(mut input_stream) => {
+                self.keep_pending_volume(
+                    AudioObjectKind::InputStream,
+                    input_stream.index,
+                    &mut input_stream.volume,
+                );
                 ignore(self.input_streams.insert(input_stream.index, input_stream));
                 Task::none()
             }
@@ -266,24 +615,32 @@ impl AudioModel<'_> {
             }
             AudioMsg::SetSourceVolume(index, channels, volume) => {
                 let current_source = self.sources.get_mut(&index)?;
+                let current = current_source.volume.first().copied().unwrap_or_default();
                 set_volume(&mut current_source.volume, volume);
-                ignore(
-                    self.audio_proxy
-                        .set_source_volume(index, channels, volume)
-                        .await,
-                );
-                Task::none()
+                let entry = rollback.and_then(|rollback| rollback.entry);
+                self.send_volume(
+                    AudioObjectKind::Source,
+                    index,
+                    channels,
+                    volume,
+                    current,
+                    entry,
+                )
             }
             AudioMsg::SetSourceMute(index, muted) => {
                 self.sources.get_mut(&index)?.muted = muted;
-                ignore(self.audio_proxy.set_source_mute(index, muted).await);
-                Task::none()
+                let proxy = self.audio_proxy.clone();
+                dbus_call(
+                    async move { proxy.set_source_mute(index, muted).await },
+                    rollback,
+                )
             }
-            AudioMsg::AddSource(source) => {
+            AudioMsg::AddSource(mut source) => {
                 if self.default_source_dummy {
                     self.default_source = source.index;
                     self.default_source_dummy = false;
                 }
+                self.keep_pending_volume(AudioObjectKind::Source, source.index, &mut source.volume);
                 ignore(self.sources.insert(source.index, source));
                 Task::none()
             }
@@ -304,31 +661,51 @@ impl AudioModel<'_> {
             }
             AudioMsg::SetOutputStreamMute(index, muted) => {
                 self.output_streams.get_mut(&index)?.muted = muted;
-                ignore(self.audio_proxy.set_output_stream_mute(index, muted).await);
-                Task::none()
+                let proxy = self.audio_proxy.clone();
+                dbus_call(
+                    async move { proxy.set_output_stream_mute(index, muted).await },
+                    rollback,
+                )
             }
             AudioMsg::SetOutputStreamVolume(index, channels, volume) => {
                 let current_output_stream = self.output_streams.get_mut(&index)?;
+                let current = current_output_stream
+                    .volume
+                    .first()
+                    .copied()
+                    .unwrap_or_default();
                 set_volume(&mut current_output_stream.volume, volume);
-                ignore(
-                    self.audio_proxy
-                        .set_output_stream_volume(index, channels, volume)
-                        .await,
-                );
-                Task::none()
+                let entry = rollback.and_then(|rollback| rollback.entry);
+                self.send_volume(
+                    AudioObjectKind::OutputStream,
+                    index,
+                    channels,
+                    volume,
+                    current,
+                    entry,
+                )
             }
             AudioMsg::SetSourceOfOutputStream(output_stream, source) => {
+                self.sources.get(&source.index)?;
                 self.output_streams
                     .get_mut(&output_stream.index)?
                     .source_index = source.index;
-                ignore(
-                    self.audio_proxy
-                        .set_source_of_output_stream(output_stream, source)
-                        .await,
-                );
-                Task::none()
+                let proxy = self.audio_proxy.clone();
+                dbus_call(
+                    async move {
+                        proxy
+                            .set_source_of_output_stream(output_stream, source)
+                            .await
+                    },
+                    rollback,
+                )
             }
-            AudioMsg::AddOutputStream(output_stream) => {
+            AudioMsg::AddOutputStream(mut output_stream) => {
+                self.keep_pending_volume(
+                    AudioObjectKind::OutputStream,
+                    output_stream.index,
+                    &mut output_stream.volume,
+                );
                 ignore(
                     self.output_streams
                         .insert(output_stream.index, output_stream),
@@ -341,20 +718,27 @@ impl AudioModel<'_> {
             }
             // TODO beforepr handle these properly when sink or source changes
             AudioMsg::SetDefaultSink(index) => {
+                let name = self.sinks.get(&index)?.name.clone();
                 self.default_sink = index;
-                let sink = self.sinks.get(&index)?;
-                ignore(self.audio_proxy.set_default_sink(sink.name.clone()).await);
-                Task::none()
+                let load_task = if matches!(self.audio_variant, AudioVariant::Equalizer) {
+                    self.load_equalizer(index)
+                } else {
+                    Task::none()
+                };
+                let proxy = self.audio_proxy.clone();
+                Task::batch([
+                    load_task,
+                    dbus_call(async move { proxy.set_default_sink(name).await }, rollback),
+                ])
             }
             AudioMsg::SetDefaultSource(index) => {
+                let name = self.sources.get(&index)?.name.clone();
                 self.default_source = index;
-                let source = self.sources.get(&index)?;
-                ignore(
-                    self.audio_proxy
-                        .set_default_source(source.name.clone())
-                        .await,
-                );
-                Task::none()
+                let proxy = self.audio_proxy.clone();
+                dbus_call(
+                    async move { proxy.set_default_source(name).await },
+                    rollback,
+                )
             }
             AudioMsg::AddAudioCard(audio_card) => {
                 ignore(self.cards.insert(audio_card.index, audio_card));
@@ -366,18 +750,316 @@ impl AudioModel<'_> {
             }
             AudioMsg::SetProfileOfCard(index, profile) => {
                 self.cards.get_mut(&index)?.active_profile = profile.clone();
-                ignore(
-                    self.audio_proxy
-                        .set_card_profile_of_device(index, profile)
-                        .await,
-                );
+                let proxy = self.audio_proxy.clone();
+                dbus_call(
+                    async move { proxy.set_card_profile_of_device(index, profile).await },
+                    rollback,
+                )
+            }
+            AudioMsg::TestSinkChannel(index, channel) => {
+                self.speaker_test = Some(SpeakerTest {
+                    sink_index: index,
+                    channel,
+                    sequential: false,
+                });
+                self.play_test_sound(index, channel)
+            }
+            AudioMsg::TestAllSinkChannels(index) => {
+                let sink = self.sinks.get(&index)?;
+                let channel = *channel_layout(sink.channels).first()?;
+                self.speaker_test = Some(SpeakerTest {
+                    sink_index: index,
+                    channel,
+                    sequential: true,
+                });
+                self.play_test_sound(index, channel)
+            }
+            AudioMsg::SinkChannelTestDone(index, channel) => {
+                let current_test = self.speaker_test?;
+                // the test was stopped or replaced in the meantime
+                if current_test.sink_index != index || current_test.channel != channel {
+                    return Some(Task::none());
+                }
+                let next_channel = if current_test.sequential {
+                    let layout = channel_layout(self.sinks.get(&index)?.channels);
+                    layout
+                        .iter()
+                        .position(|value| *value == channel)
+                        .and_then(|position| layout.get(position + 1))
+                        .copied()
+                } else {
+                    None
+                };
+                match next_channel {
+                    Some(next_channel) => {
+                        self.speaker_test = Some(SpeakerTest {
+                            channel: next_channel,
+                            ..current_test
+                        });
+                        self.play_test_sound(index, next_channel)
+                    }
+                    None => {
+                        self.speaker_test = None;
+                        Task::none()
+                    }
+                }
+            }
+            AudioMsg::StopSinkTest => {
+                self.speaker_test = None;
+                Task::none()
+            }
+            AudioMsg::SetEqualizerEnabled(index, enabled) => {
+                let equalizer = self.equalizers.get_mut(&index)?;
+                let previous = equalizer.clone();
+                equalizer.enabled = enabled;
+                self.apply_equalizer(index, previous)?
+            }
+            AudioMsg::SetEqualizerBand(index, band, gain) => {
+                let equalizer = self.equalizers.get_mut(&index)?;
+                let previous = equalizer.clone();
+                *equalizer.gains.get_mut(band)? = gain;
+                equalizer.preset = CUSTOM_PRESET.to_string();
+                self.apply_equalizer(index, previous)?
+            }
+            AudioMsg::SetEqualizerPreset(index, preset_name) => {
+                let preset = find_preset(&preset_name)?;
+                let equalizer = self.equalizers.get_mut(&index)?;
+                let previous = equalizer.clone();
+                equalizer.gains = preset.gains.to_vec();
+                equalizer.preset = preset_name;
+                self.apply_equalizer(index, previous)?
+            }
+            AudioMsg::ReceiveEqualizer(index, equalizer) => {
+                ignore(self.equalizers.insert(index, equalizer));
                 Task::none()
             }
+            AudioMsg::EqualizerSent(index, result) => {
+                let sent = match result {
+                    Ok(equalizer) => Some(equalizer),
+                    Err(message) => {
+                        self.error = Some(message);
+                        None
+                    }
+                };
+                match self.equalizer_queue.finish(index, sent) {
+                    EqualizerQueueState::Next => self.equalizer_call(index)?,
+                    EqualizerQueueState::Done => Task::none(),
+                    EqualizerQueueState::RollBack(equalizer) => {
+                        ignore(self.equalizers.insert(index, equalizer));
+                        Task::none()
+                    }
+                }
+            }
+            AudioMsg::VolumeSent(kind, index, result) => {
+                let sent = match result {
+                    Ok(volume) => Some(volume),
+                    Err(message) => {
+                        self.error = Some(message);
+                        None
+                    }
+                };
+                match self.volume_queue.finish(kind, index, sent) {
+                    VolumeQueueState::Next(channels, volume) => {
+                        self.volume_call(kind, index, channels, volume)
+                    }
+                    VolumeQueueState::Done => Task::none(),
+                    VolumeQueueState::RollBack(volume, entries) => {
+                        // the lost changes can not be undone either
+                        for entry in entries {
+                            self.history.discard(entry);
+                        }
+                        set_volume(self.volume_mut(kind, index)?, volume);
+                        Task::none()
+                    }
+                }
+            }
+            AudioMsg::RestoreVolume(kind, index, channels, volume) => {
+                let current_volume = self.volume_mut(kind, index)?;
+                let current = current_volume.first().copied().unwrap_or_default();
+                // the daemon takes one volume for all channels, the loudest one is sent
+                // and shown, so the channels match what the daemon applied
+                let sent = volume.iter().max().copied().unwrap_or(current);
+                set_volume(current_volume, sent);
+                let entry = rollback.and_then(|rollback| rollback.entry);
+                self.send_volume(kind, index, channels, sent, current, entry)
+            }
+            AudioMsg::CallFailed(message, rollback) => {
+                self.error = Some(message);
+                if let Some(rollback) = rollback {
+                    // the change never happened, so it can not be undone either
+                    if let Some(entry) = rollback.entry {
+                        self.history.discard(entry);
+                    }
+                    // only the local state is reverted, the daemon never applied the change
+                    ignore(self.apply(rollback.msg, None));
+                }
+                Task::none()
+            }
+            AudioMsg::DismissError => {
+                self.error = None;
+                Task::none()
+            }
+            AudioMsg::EnterPage => Task::none(),
+            AudioMsg::LeavePage => {
+                // the remaining test sounds are not played once the page is hidden
+                self.speaker_test = None;
+                Task::none()
+            }
+            // handled in update
+            AudioMsg::Undo | AudioMsg::Redo | AudioMsg::DismissToast(_) => Task::none(),
         };
         Some(cmd)
     }
 
-    // TODO beforepr handle errors
+    /// Sends a volume change, unless a call for the same object is still running.
+    /// In that case only the latest value is sent once that call has returned.
+    fn send_volume(
+        &mut self,
+        kind: AudioObjectKind,
+        index: u32,
+        channels: u16,
+        volume: u32,
+        current: u32,
+        entry: Option<u32>,
+    ) -> Task<ReSetMessage> {
+        if self
+            .volume_queue
+            .push(kind, index, channels, volume, current, entry)
+        {
+            self.volume_call(kind, index, channels, volume)
+        } else {
+            Task::none()
+        }
+    }
+
+    fn volume_call(
+        &self,
+        kind: AudioObjectKind,
+        index: u32,
+        channels: u16,
+        volume: u32,
+    ) -> Task<ReSetMessage> {
+        let proxy = self.audio_proxy.clone();
+        Task::future(async move {
+            let result = match kind {
+                AudioObjectKind::Sink => proxy.set_sink_volume(index, channels, volume).await,
+                AudioObjectKind::Source => proxy.set_source_volume(index, channels, volume).await,
+                AudioObjectKind::InputStream => {
+                    proxy.set_input_stream_volume(index, channels, volume).await
+                }
+                AudioObjectKind::OutputStream => {
+                    proxy
+                        .set_output_stream_volume(index, channels, volume)
+                        .await
+                }
+            };
+            let result = result.map(|_| volume).map_err(daemon_error_message);
+            wrap(AudioMsg::VolumeSent(kind, index, result))
+        })
+    }
+
+    /// Signals sent while a slider is dragged carry outdated volumes,
+    /// the local value is kept until all pending changes were sent.
+    fn keep_pending_volume(&mut self, kind: AudioObjectKind, index: u32, volume: &mut Vec<u32>) {
+        if !self.volume_queue.is_pending(kind, index) {
+            return;
+        }
+        if let Some(local_volume) = self.volume_mut(kind, index) {
+            volume.clone_from(local_volume);
+        }
+    }
+
+    fn volume_mut(&mut self, kind: AudioObjectKind, index: u32) -> Option<&mut Vec<u32>> {
+        match kind {
+            AudioObjectKind::Sink => self.sinks.get_mut(&index).map(|sink| &mut sink.volume),
+            AudioObjectKind::Source => self
+                .sources
+                .get_mut(&index)
+                .map(|source| &mut source.volume),
+            AudioObjectKind::InputStream => self
+                .input_streams
+                .get_mut(&index)
+                .map(|input_stream| &mut input_stream.volume),
+            AudioObjectKind::OutputStream => self
+                .output_streams
+                .get_mut(&index)
+                .map(|output_stream| &mut output_stream.volume),
+        }
+    }
+
+    /// Fetches the equalizer of a sink from the daemon, unless it is already known
+    fn load_equalizer(&self, index: u32) -> Task<ReSetMessage> {
+        if self.equalizers.contains_key(&index) {
+            return Task::none();
+        }
+        if let Some(sink) = self.sinks.get(&index) {
+            let name = sink.name.clone();
+            let proxy = self.audio_proxy.clone();
+            Task::future(async move {
+                match proxy.get_sink_equalizer(name).await {
+                    Ok(equalizer) => wrap(AudioMsg::ReceiveEqualizer(index, equalizer)),
+                    // fall back to a flat equalizer so the page stays usable
+                    Err(error) => wrap(AudioMsg::CallFailed(
+                        daemon_error_message(error),
+                        Some(Box::new(Rollback {
+                            msg: AudioMsg::ReceiveEqualizer(index, AudioEqualizer::default()),
+                            entry: None,
+                        })),
+                    )),
+                }
+            })
+        } else {
+            Task::none()
+        }
+    }
+
+    /// The daemon owns the filter chain, which keeps it alive after ReSet is closed.
+    /// While a call is running only the latest equalizer is sent once it returned.
+    fn apply_equalizer(
+        &mut self,
+        index: u32,
+        previous: AudioEqualizer,
+    ) -> Option<Task<ReSetMessage>> {
+        self.sinks.get(&index)?;
+        if self.equalizer_queue.push(index, previous) {
+            self.equalizer_call(index)
+        } else {
+            Some(Task::none())
+        }
+    }
+
+    fn equalizer_call(&self, index: u32) -> Option<Task<ReSetMessage>> {
+        let name = self.sinks.get(&index)?.name.clone();
+        let equalizer = self.equalizers.get(&index)?.clone();
+        let proxy = self.audio_proxy.clone();
+        Some(Task::future(async move {
+            let result = proxy
+                .set_sink_equalizer(name, equalizer.clone())
+                .await
+                .map(|_| equalizer)
+                .map_err(daemon_error_message);
+            wrap(AudioMsg::EqualizerSent(index, result))
+        }))
+    }
+
+    fn play_test_sound(&self, index: u32, channel: AudioChannel) -> Task<ReSetMessage> {
+        let proxy = self.audio_proxy.clone();
+        let channel_name = channel.position_name().to_string();
+        Task::future(async move {
+            if let Err(error) = proxy.play_test_sound(index, channel_name).await {
+                return wrap(AudioMsg::CallFailed(
+                    daemon_error_message(error),
+                    Some(Box::new(Rollback {
+                        msg: AudioMsg::StopSinkTest,
+                        entry: None,
+                    })),
+                ));
+            }
+            async_io::Timer::after(TEST_SOUND_DURATION).await;
+            wrap(AudioMsg::SinkChannelTestDone(index, channel))
+        })
+    }
+
     pub fn view(&self) -> Option<Element<ReSetMessage>> {
         let cards = {
             let card_elements: Vec<Element<ReSetMessage>> = self
@@ -398,21 +1080,53 @@ impl AudioModel<'_> {
         // TODO beforepr, should these be combined??
         let devices = {
             row!(
-                device_card_view(self.default_source, &self.sources),
-                device_card_view(self.default_sink, &self.sinks)
+                device_card_view(self.default_source, &self.sources, self.speaker_test),
+                device_card_view(self.default_sink, &self.sinks, self.speaker_test)
             )
             .spacing(20)
             .into()
         };
+        let equalizer = match (
+            self.sinks.get(&self.default_sink),
+            self.equalizers.get(&self.default_sink),
+        ) {
+            (Some(sink), Some(equalizer)) => equalizer_view(sink, equalizer),
+            _ => column!(text("No output device available")).into(),
+        };
         let base = match self.audio_variant {
             AudioVariant::Cards => cards,
             AudioVariant::Input => input,
             AudioVariant::Output => output,
             AudioVariant::InputAndOutput => row![output, input].into(),
             AudioVariant::Devices => devices,
+            AudioVariant::Equalizer => equalizer,
         };
         // Make an enum to buttons function
-        Some(column![base].padding(20).into())
+        let error = self
+            .error
+            .as_deref()
+            .map(|message| error_banner(message, wrap(AudioMsg::DismissError)));
+        let toast = self
+            .toast
+            .as_ref()
+            .map(|toast| toast_view(toast, wrap(AudioMsg::DismissToast(toast.id))));
+        Some(
+            column![]
+                .push_maybe(error)
+                .push_maybe(toast)
+                .push(base)
+                .spacing(10)
+                .padding(20)
+                .into(),
+        )
+    }
+}
+
+fn mute_description(muted: bool, name: &str) -> String {
+    if muted {
+        format!("Muted {}", name)
+    } else {
+        format!("Unmuted {}", name)
     }
 }
 
@@ -1,14 +1,18 @@
 use std::fmt::Display;
 
+use iced::Element;
 use serde::{Deserialize, Serialize};
 use zbus::{proxy, zvariant::Type};
 
-use crate::components::{
-    audio_card::{TCardUser, TStreamCardUser},
-    icons::Icon,
+use crate::{
+    components::{
+        audio_card::{speaker_test_view, TCardUser, TStreamCardUser},
+        icons::Icon,
+    },
+    ReSetMessage,
 };
 
-use super::audio_impl::AudioMsg;
+use super::{audio_impl::AudioMsg, equalizer::EQUALIZER_BANDS, speaker_test::SpeakerTest};
 
 pub trait TIndex {
     fn index(&self) -> u32;
@@ -95,6 +99,13 @@ impl TCardUser for AudioSink {
     fn title() -> String {
         "Output".to_string()
     }
+
+    fn speaker_test<'a>(
+        &self,
+        speaker_test: Option<SpeakerTest>,
+    ) -> Option<Element<'a, ReSetMessage>> {
+        Some(speaker_test_view(self.index, self.channels, speaker_test))
+    }
 }
 
 impl Display for AudioSink {
@@ -330,7 +341,7 @@ impl TIndex for OutputStream {
     }
 }
 
-#[derive(Debug, Clone, Default, Deserialize, Serialize, Type)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Type, PartialEq)]
 pub struct AudioCard {
     pub index: u32,
     pub name: String,
@@ -344,13 +355,30 @@ impl TIndex for AudioCard {
     }
 }
 
-#[derive(Debug, Clone, Default, Deserialize, Serialize, Type)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Type, PartialEq)]
 pub struct AudioCardProfile {
     pub name: String,
     pub description: String,
     pub available: bool,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, Type, PartialEq)]
+pub struct AudioEqualizer {
+    pub enabled: bool,
+    pub preset: String,
+    pub gains: Vec<f64>,
+}
+
+impl Default for AudioEqualizer {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            preset: "Flat".to_string(),
+            gains: vec![0.0; EQUALIZER_BANDS.len()],
+        }
+    }
+}
+
 // TODO beforepr finish this
 // TODO beforepr this needs to be put into the lib as the type cant be reused
 #[proxy(
@@ -400,6 +428,9 @@ pub trait AudioDbus {
     fn set_sink_volume(&self, index: u32, channels: u16, volume: u32) -> zbus::Result<()>;
     fn set_sink_mute(&self, index: u32, muted: bool) -> zbus::Result<()>;
     fn set_default_sink(&self, sink: String) -> zbus::Result<AudioSink>;
+    fn play_test_sound(&self, sink_index: u32, channel: String) -> zbus::Result<()>;
+    fn get_sink_equalizer(&self, sink: String) -> zbus::Result<AudioEqualizer>;
+    fn set_sink_equalizer(&self, sink: String, equalizer: AudioEqualizer) -> zbus::Result<()>;
 
     fn list_sources(&self) -> zbus::Result<Vec<AudioSource>>;
     fn get_default_source(&self) -> zbus::Result<AudioSource>;
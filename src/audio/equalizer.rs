@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use super::dbus_interface::AudioEqualizer;
+
+/// Center frequencies of the graphic equalizer bands in Hz
+pub const EQUALIZER_BANDS: [u32; 10] = [31, 62, 125, 250, 500, 1000, 2000, 4000, 8000, 16000];
+/// Maximum boost or cut of a single band in dB
+pub const MAX_GAIN: f64 = 12.0;
+/// Preset name used once a band was changed by hand
+pub const CUSTOM_PRESET: &str = "Custom";
+
+pub struct EqualizerPreset {
+    pub name: &'static str,
+    pub gains: [f64; EQUALIZER_BANDS.len()],
+}
+
+pub const EQUALIZER_PRESETS: [EqualizerPreset; 7] = [
+    EqualizerPreset {
+        name: "Flat",
+        gains: [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    },
+    EqualizerPreset {
+        name: "Bass Boost",
+        gains: [6.0, 5.0, 4.0, 2.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0],
+    },
+    EqualizerPreset {
+        name: "Treble Boost",
+        gains: [0.0, 0.0, 0.0, 0.0, 0.0, 0.5, 2.0, 4.0, 5.0, 6.0],
+    },
+    EqualizerPreset {
+        name: "Vocal",
+        gains: [-2.0, -2.0, -1.0, 1.0, 3.0, 3.5, 3.0, 1.5, 0.0, -1.0],
+    },
+    EqualizerPreset {
+        name: "Rock",
+        gains: [4.5, 3.5, 2.0, -0.5, -1.5, -1.0, 1.0, 2.5, 3.5, 4.0],
+    },
+    EqualizerPreset {
+        name: "Classical",
+        gains: [3.5, 3.0, 2.0, 1.0, -0.5, -0.5, 0.0, 1.5, 2.5, 3.0],
+    },
+    EqualizerPreset {
+        name: "Speech",
+        gains: [-6.0, -4.0, -1.0, 1.5, 3.0, 4.0, 3.5, 2.0, -1.0, -4.0],
+    },
+];
+
+pub fn find_preset(name: &str) -> Option<&'static EqualizerPreset> {
+    EQUALIZER_PRESETS.iter().find(|preset| preset.name == name)
+}
+
+pub fn band_label(frequency: u32) -> String {
+    if frequency >= 1000 {
+        format!("{}k", frequency / 1000)
+    } else {
+        frequency.to_string()
+    }
+}
+
+pub enum EqualizerQueueState {
+    /// The equalizer changed while the call was running, the current one has to be sent now
+    Next,
+    Done,
+    /// The call failed and nothing else is queued, the local equalizer is reset to this one
+    RollBack(AudioEqualizer),
+}
+
+#[derive(Debug)]
+struct PendingEqualizer {
+    /// Last equalizer the daemon is known to have applied
+    confirmed: AudioEqualizer,
+    queued: bool,
+}
+
+/// Keeps at most one equalizer call per sink in flight, like the volume queue.
+/// Changes arriving in the meantime are sent together once that call has returned.
+#[derive(Debug, Default)]
+pub struct EqualizerQueue {
+    pending: HashMap<u32, PendingEqualizer>,
+}
+
+impl EqualizerQueue {
+    /// Returns true if the equalizer can be sent right away.
+    /// `current` is the equalizer before this change was applied locally.
+    pub fn push(&mut self, index: u32, current: AudioEqualizer) -> bool {
+        match self.pending.get_mut(&index) {
+            Some(pending) => {
+                pending.queued = true;
+                false
+            }
+            None => {
+                self.pending.insert(
+                    index,
+                    PendingEqualizer {
+                        confirmed: current,
+                        queued: false,
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    /// Marks the running call as done, `sent` is None if the call failed
+    pub fn finish(&mut self, index: u32, sent: Option<AudioEqualizer>) -> EqualizerQueueState {
+        let pending = match self.pending.get_mut(&index) {
+            Some(pending) => pending,
+            None => return EqualizerQueueState::Done,
+        };
+        let failed = sent.is_none();
+        if let Some(equalizer) = sent {
+            pending.confirmed = equalizer;
+        }
+        if pending.queued {
+            pending.queued = false;
+            return EqualizerQueueState::Next;
+        }
+        let confirmed = pending.confirmed.clone();
+        self.pending.remove(&index);
+        if failed {
+            EqualizerQueueState::RollBack(confirmed)
+        } else {
+            EqualizerQueueState::Done
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn equalizer(gain: f64) -> AudioEqualizer {
+        AudioEqualizer {
+            enabled: true,
+            preset: CUSTOM_PRESET.to_string(),
+            gains: vec![gain; EQUALIZER_BANDS.len()],
+        }
+    }
+
+    #[test]
+    fn coalesces_changes_while_a_call_runs() {
+        let mut queue = EqualizerQueue::default();
+        assert!(queue.push(1, equalizer(0.0)));
+        assert!(!queue.push(1, equalizer(1.0)));
+        assert!(!queue.push(1, equalizer(2.0)));
+        // other sinks have their own call
+        assert!(queue.push(2, equalizer(0.0)));
+        assert!(matches!(
+            queue.finish(1, Some(equalizer(1.0))),
+            EqualizerQueueState::Next
+        ));
+        assert!(matches!(
+            queue.finish(1, Some(equalizer(2.0))),
+            EqualizerQueueState::Done
+        ));
+        assert!(queue.push(1, equalizer(2.0)));
+    }
+
+    #[test]
+    fn rolls_back_to_the_confirmed_equalizer() {
+        let mut queue = EqualizerQueue::default();
+        assert!(queue.push(1, equalizer(0.0)));
+        match queue.finish(1, None) {
+            EqualizerQueueState::RollBack(confirmed) => assert_eq!(confirmed, equalizer(0.0)),
+            _ => panic!("expected a rollback"),
+        }
+
+        assert!(queue.push(1, equalizer(0.0)));
+        assert!(!queue.push(1, equalizer(1.0)));
+        assert!(matches!(
+            queue.finish(1, Some(equalizer(1.0))),
+            EqualizerQueueState::Next
+        ));
+        match queue.finish(1, None) {
+            EqualizerQueueState::RollBack(confirmed) => assert_eq!(confirmed, equalizer(1.0)),
+            _ => panic!("expected a rollback"),
+        }
+    }
+}
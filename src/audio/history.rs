@@ -0,0 +1,185 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use super::audio_impl::AudioMsg;
+
+/// Amount of changes that can be undone
+const HISTORY_LIMIT: usize = 50;
+/// Changes to the same volume within this window are merged into one entry,
+/// this makes a slider drag undoable in one step
+const MERGE_WINDOW: Duration = Duration::from_millis(1000);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioObjectKind {
+    Sink,
+    Source,
+    InputStream,
+    OutputStream,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    /// Assigned when the entry is recorded, identifies it in both stacks
+    id: u32,
+    pub description: String,
+    pub undo: AudioMsg,
+    pub redo: AudioMsg,
+    /// Set for volume changes, entries with the same key can be merged
+    pub merge_key: Option<(AudioObjectKind, u32)>,
+    recorded_at: Instant,
+}
+
+impl HistoryEntry {
+    pub fn new(description: impl Into<String>, undo: AudioMsg, redo: AudioMsg) -> Self {
+        Self {
+            id: 0,
+            description: description.into(),
+            undo,
+            redo,
+            merge_key: None,
+            recorded_at: Instant::now(),
+        }
+    }
+
+    pub fn merge_key(mut self, kind: AudioObjectKind, index: u32) -> Self {
+        self.merge_key = Some((kind, index));
+        self
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AudioHistory {
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    next_id: u32,
+}
+
+impl AudioHistory {
+    /// Returns the id of the entry holding the change, merged changes share the id
+    pub fn record(&mut self, mut entry: HistoryEntry) -> u32 {
+        self.redo_stack.clear();
+        if let Some(last) = self.undo_stack.back_mut() {
+            if entry.merge_key.is_some()
+                && last.merge_key == entry.merge_key
+                && entry.recorded_at.duration_since(last.recorded_at) < MERGE_WINDOW
+            {
+                // keep the original undo, only the target of the change moves
+                last.redo = entry.redo;
+                last.recorded_at = entry.recorded_at;
+                return last.id;
+            }
+        }
+        self.next_id = self.next_id.wrapping_add(1);
+        entry.id = self.next_id;
+        self.undo_stack.push_back(entry);
+        if self.undo_stack.len() > HISTORY_LIMIT {
+            self.undo_stack.pop_front();
+        }
+        self.next_id
+    }
+
+    pub fn undo(&mut self) -> Option<HistoryEntry> {
+        let entry = self.undo_stack.pop_back()?;
+        self.redo_stack.push(entry.clone());
+        Some(entry)
+    }
+
+    pub fn redo(&mut self) -> Option<HistoryEntry> {
+        let entry = self.redo_stack.pop()?;
+        self.undo_stack.push_back(entry.clone());
+        Some(entry)
+    }
+
+    /// Removes the entry of a change the daemon rejected, wherever it is now
+    pub fn discard(&mut self, id: u32) {
+        self.undo_stack.retain(|entry| entry.id != id);
+        self.redo_stack.retain(|entry| entry.id != id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn volume(volume: u32, previous: u32) -> HistoryEntry {
+        HistoryEntry::new(
+            "Volume",
+            AudioMsg::RestoreVolume(AudioObjectKind::Sink, 1, 2, vec![previous; 2]),
+            AudioMsg::SetSinkVolume(1, 2, volume),
+        )
+        .merge_key(AudioObjectKind::Sink, 1)
+    }
+
+    fn mute(muted: bool) -> HistoryEntry {
+        HistoryEntry::new(
+            if muted { "Muted" } else { "Unmuted" },
+            AudioMsg::SetSinkMute(1, !muted),
+            AudioMsg::SetSinkMute(1, muted),
+        )
+    }
+
+    #[test]
+    fn merges_volume_changes() {
+        let mut history = AudioHistory::default();
+        let first = history.record(volume(50, 40));
+        assert_eq!(history.record(volume(60, 50)), first);
+        let entry = history.undo().unwrap();
+        assert_eq!(entry.id(), first);
+        assert_eq!(
+            entry.undo,
+            AudioMsg::RestoreVolume(AudioObjectKind::Sink, 1, 2, vec![40; 2])
+        );
+        assert_eq!(entry.redo, AudioMsg::SetSinkVolume(1, 2, 60));
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn keeps_other_changes_apart() {
+        let mut history = AudioHistory::default();
+        let first = history.record(volume(50, 40));
+        let muted = history.record(mute(true));
+        let second = history.record(volume(60, 50));
+        assert_ne!(first, muted);
+        assert_ne!(muted, second);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn discards_the_rejected_entry() {
+        let mut history = AudioHistory::default();
+        let muted = history.record(mute(true));
+        let unmuted = history.record(mute(false));
+        // both entries revert to a mute message, only the id tells them apart
+        history.discard(unmuted);
+        let entry = history.undo().unwrap();
+        assert_eq!(entry.id(), muted);
+        assert_eq!(entry.description, "Muted");
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn discards_undone_entries() {
+        let mut history = AudioHistory::default();
+        let muted = history.record(mute(true));
+        history.record(mute(false));
+        assert_eq!(history.undo().unwrap().description, "Unmuted");
+        history.discard(muted);
+        assert!(history.undo().is_none());
+        assert_eq!(history.redo().unwrap().description, "Unmuted");
+    }
+
+    #[test]
+    fn recording_clears_redo() {
+        let mut history = AudioHistory::default();
+        history.record(mute(true));
+        history.undo();
+        history.record(mute(false));
+        assert!(history.redo().is_none());
+    }
+}
@@ -1,2 +1,6 @@
 pub mod audio_impl;
 pub mod dbus_interface;
+pub mod equalizer;
+pub mod history;
+pub mod speaker_test;
+pub mod volume_queue;
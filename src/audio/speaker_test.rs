@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+/// Time each channel is given to play its test sound before the next one starts.
+pub const TEST_SOUND_DURATION: Duration = Duration::from_millis(1500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioChannel {
+    Mono,
+    FrontLeft,
+    FrontRight,
+    FrontCenter,
+    Lfe,
+    RearLeft,
+    RearRight,
+    SideLeft,
+    SideRight,
+}
+
+impl AudioChannel {
+    /// Channel position name as used by PulseAudio/PipeWire
+    pub fn position_name(&self) -> &'static str {
+        match self {
+            AudioChannel::Mono => "mono",
+            AudioChannel::FrontLeft => "front-left",
+            AudioChannel::FrontRight => "front-right",
+            AudioChannel::FrontCenter => "front-center",
+            AudioChannel::Lfe => "lfe",
+            AudioChannel::RearLeft => "rear-left",
+            AudioChannel::RearRight => "rear-right",
+            AudioChannel::SideLeft => "side-left",
+            AudioChannel::SideRight => "side-right",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AudioChannel::Mono => "Mono",
+            AudioChannel::FrontLeft => "Front Left",
+            AudioChannel::FrontRight => "Front Right",
+            AudioChannel::FrontCenter => "Center",
+            AudioChannel::Lfe => "LFE",
+            AudioChannel::RearLeft => "Surround Left",
+            AudioChannel::RearRight => "Surround Right",
+            AudioChannel::SideLeft => "Side Left",
+            AudioChannel::SideRight => "Side Right",
+        }
+    }
+}
+
+/// Default channel layout for a device with the given amount of channels.
+pub fn channel_layout(channels: u16) -> Vec<AudioChannel> {
+    use AudioChannel::*;
+    match channels {
+        0 => Vec::new(),
+        1 => vec![Mono],
+        2 => vec![FrontLeft, FrontRight],
+        3 => vec![FrontLeft, FrontRight, Lfe],
+        4 => vec![FrontLeft, FrontRight, RearLeft, RearRight],
+        5 => vec![FrontLeft, FrontRight, FrontCenter, RearLeft, RearRight],
+        _ => vec![
+            FrontLeft,
+            FrontRight,
+            FrontCenter,
+            Lfe,
+            RearLeft,
+            RearRight,
+            SideLeft,
+            SideRight,
+        ]
+        .into_iter()
+        .take(channels as usize)
+        .collect(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeakerTest {
+    pub sink_index: u32,
+    pub channel: AudioChannel,
+    /// Continue with the next channel of the layout once this one is done
+    pub sequential: bool,
+}
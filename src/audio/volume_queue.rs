@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use super::history::AudioObjectKind;
+
+pub enum VolumeQueueState {
+    /// Another value arrived while the call was running and has to be sent now
+    Next(u16, u32),
+    Done,
+    /// The call failed and nothing else is queued, the local volume is reset to this value
+    /// and the history entries of the lost changes are dropped
+    RollBack(u32, Vec<u32>),
+}
+
+#[derive(Debug)]
+struct PendingVolume {
+    /// Last volume the daemon is known to have applied
+    confirmed: u32,
+    queued: Option<(u16, u32)>,
+    /// History entries of the sent changes that are not confirmed yet
+    entries: Vec<u32>,
+    /// History entries of the queued change
+    queued_entries: Vec<u32>,
+}
+
+/// Merged changes share their entry, it is only kept once
+fn add_entry(entries: &mut Vec<u32>, entry: u32) {
+    if !entries.contains(&entry) {
+        entries.push(entry);
+    }
+}
+
+/// Keeps at most one volume call per audio object in flight.
+/// Values arriving in the meantime replace each other, only the latest one is sent.
+#[derive(Debug, Default)]
+pub struct VolumeQueue {
+    pending: HashMap<(AudioObjectKind, u32), PendingVolume>,
+}
+
+impl VolumeQueue {
+    /// Returns true if the volume can be sent right away.
+    /// `current` is the volume before this change was applied locally.
+    pub fn push(
+        &mut self,
+        kind: AudioObjectKind,
+        index: u32,
+        channels: u16,
+        volume: u32,
+        current: u32,
+        entry: Option<u32>,
+    ) -> bool {
+        let send = !self.pending.contains_key(&(kind, index));
+        let pending = self.pending.entry((kind, index)).or_insert(PendingVolume {
+            confirmed: current,
+            queued: None,
+            entries: Vec::new(),
+            queued_entries: Vec::new(),
+        });
+        if !send {
+            pending.queued = Some((channels, volume));
+        }
+        if let Some(entry) = entry {
+            if send {
+                add_entry(&mut pending.entries, entry);
+            } else {
+                add_entry(&mut pending.queued_entries, entry);
+            }
+        }
+        send
+    }
+
+    /// Marks the running call as done, `sent` is None if the call failed
+    pub fn finish(
+        &mut self,
+        kind: AudioObjectKind,
+        index: u32,
+        sent: Option<u32>,
+    ) -> VolumeQueueState {
+        let pending = match self.pending.get_mut(&(kind, index)) {
+            Some(pending) => pending,
+            None => return VolumeQueueState::Done,
+        };
+        if let Some(volume) = sent {
+            pending.confirmed = volume;
+            pending.entries.clear();
+        }
+        match pending.queued.take() {
+            Some((channels, volume)) => {
+                for entry in std::mem::take(&mut pending.queued_entries) {
+                    add_entry(&mut pending.entries, entry);
+                }
+                VolumeQueueState::Next(channels, volume)
+            }
+            None => {
+                let confirmed = pending.confirmed;
+                let entries = std::mem::take(&mut pending.entries);
+                self.pending.remove(&(kind, index));
+                if sent.is_some() {
+                    VolumeQueueState::Done
+                } else {
+                    VolumeQueueState::RollBack(confirmed, entries)
+                }
+            }
+        }
+    }
+
+    pub fn is_pending(&self, kind: AudioObjectKind, index: u32) -> bool {
+        self.pending.contains_key(&(kind, index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SINK: AudioObjectKind = AudioObjectKind::Sink;
+
+    #[test]
+    fn coalesces_volumes_while_a_call_runs() {
+        let mut queue = VolumeQueue::default();
+        assert!(queue.push(SINK, 1, 2, 50, 40, None));
+        assert!(!queue.push(SINK, 1, 2, 60, 50, None));
+        assert!(!queue.push(SINK, 1, 2, 70, 60, None));
+        // other objects have their own call
+        assert!(queue.push(AudioObjectKind::Source, 1, 2, 30, 20, None));
+        assert!(matches!(
+            queue.finish(SINK, 1, Some(50)),
+            VolumeQueueState::Next(2, 70)
+        ));
+        assert!(matches!(
+            queue.finish(SINK, 1, Some(70)),
+            VolumeQueueState::Done
+        ));
+        assert!(!queue.is_pending(SINK, 1));
+        assert!(queue.is_pending(AudioObjectKind::Source, 1));
+    }
+
+    #[test]
+    fn rolls_back_to_the_confirmed_volume() {
+        let mut queue = VolumeQueue::default();
+        assert!(queue.push(SINK, 1, 2, 50, 40, Some(1)));
+        assert!(!queue.push(SINK, 1, 2, 60, 50, Some(2)));
+        // the queued volume is still sent after a failure
+        assert!(matches!(
+            queue.finish(SINK, 1, None),
+            VolumeQueueState::Next(2, 60)
+        ));
+        match queue.finish(SINK, 1, None) {
+            VolumeQueueState::RollBack(volume, entries) => {
+                assert_eq!(volume, 40);
+                assert_eq!(entries, vec![1, 2]);
+            }
+            _ => panic!("expected a rollback"),
+        }
+        assert!(!queue.is_pending(SINK, 1));
+    }
+
+    #[test]
+    fn rolls_back_only_unconfirmed_entries() {
+        let mut queue = VolumeQueue::default();
+        assert!(queue.push(SINK, 1, 2, 50, 40, Some(1)));
+        assert!(!queue.push(SINK, 1, 2, 60, 50, Some(2)));
+        assert!(!queue.push(SINK, 1, 2, 65, 60, Some(2)));
+        assert!(matches!(
+            queue.finish(SINK, 1, Some(50)),
+            VolumeQueueState::Next(2, 65)
+        ));
+        match queue.finish(SINK, 1, None) {
+            VolumeQueueState::RollBack(volume, entries) => {
+                assert_eq!(volume, 50);
+                assert_eq!(entries, vec![2]);
+            }
+            _ => panic!("expected a rollback"),
+        }
+    }
+
+    #[test]
+    fn ignores_unknown_calls() {
+        let mut queue = VolumeQueue::default();
+        assert!(matches!(
+            queue.finish(SINK, 1, None),
+            VolumeQueueState::Done
+        ));
+    }
+}
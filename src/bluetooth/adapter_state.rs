@@ -0,0 +1,45 @@
+use super::dbus_interface::{BluetoothAdapter, BluetoothAdapterDetails};
+
+/// Whether Bluetooth can be used right now, and if not, why
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdapterState {
+    NoAdapter,
+    /// Disabled by rfkill or a hardware switch
+    Blocked,
+    PoweredOff,
+    Ready,
+}
+
+impl AdapterState {
+    /// Without details, e.g. from an older daemon, a blocked adapter shows as powered off
+    pub fn of(
+        adapter: Option<&BluetoothAdapter>,
+        details: Option<&BluetoothAdapterDetails>,
+    ) -> Self {
+        let blocked = details.is_some_and(|details| details.power_state == "off-blocked");
+        match adapter {
+            None => Self::NoAdapter,
+            Some(_) if blocked => Self::Blocked,
+            Some(adapter) if !adapter.powered => Self::PoweredOff,
+            Some(_) => Self::Ready,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::NoAdapter => "No Bluetooth adapter found",
+            Self::Blocked => "Bluetooth is blocked",
+            Self::PoweredOff => "Bluetooth is turned off",
+            Self::Ready => "Bluetooth is ready",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::NoAdapter => "Plug in a Bluetooth adapter, it is picked up automatically.",
+            Self::Blocked => "The adapter is disabled by rfkill or a hardware switch.",
+            Self::PoweredOff => "Turn on the adapter to find and connect devices.",
+            Self::Ready => "",
+        }
+    }
+}
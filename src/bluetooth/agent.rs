@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use zbus::zvariant::OwnedObjectPath;
+
+/// Requests that are not answered within this time are canceled
+pub const AGENT_TIMEOUT: Duration = Duration::from_secs(30);
+/// BlueZ accepts legacy PIN codes with 1 to 16 characters
+pub const MAX_PIN_LENGTH: usize = 16;
+
+/// Requests of the BlueZ pairing agent, forwarded by the daemon
+#[derive(Debug, Clone)]
+pub enum AgentRequest {
+    PinCode(OwnedObjectPath),
+    /// Passkey and the amount of digits already entered on the device
+    DisplayPasskey(OwnedObjectPath, u32, u16),
+    Confirmation(OwnedObjectPath, u32),
+    /// UUID of the service that wants to be authorized
+    AuthorizeService(OwnedObjectPath, String),
+}
+
+impl AgentRequest {
+    pub fn device(&self) -> &OwnedObjectPath {
+        match self {
+            AgentRequest::PinCode(device)
+            | AgentRequest::DisplayPasskey(device, _, _)
+            | AgentRequest::Confirmation(device, _)
+            | AgentRequest::AuthorizeService(device, _) => device,
+        }
+    }
+}
+
+pub struct PendingAgentRequest {
+    /// Used to ignore timeouts of requests that were already answered
+    pub id: u32,
+    pub request: AgentRequest,
+    pub pin: String,
+}
+
+impl PendingAgentRequest {
+    pub fn valid_pin(&self) -> bool {
+        !self.pin.is_empty() && self.pin.len() <= MAX_PIN_LENGTH
+    }
+}
+
+/// Passkeys are always shown with 6 digits, including leading zeros
+pub fn format_passkey(passkey: u32) -> String {
+    format!("{:06}", passkey)
+}
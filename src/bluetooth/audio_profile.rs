@@ -0,0 +1,140 @@
+use std::fmt::Display;
+
+use crate::audio::dbus_interface::{AudioCard, AudioCardProfile};
+
+/// Prefix of the cards the audio server creates for Bluetooth devices
+const BLUEZ_CARD_PREFIX: &str = "bluez_card.";
+/// Profile names without the codec, e.g. a2dp-sink-aac is a2dp-sink with the AAC codec
+const PROFILE_BASES: [&str; 6] = [
+    "a2dp-sink",
+    "a2dp-source",
+    "headset-head-unit",
+    "headset-audio-gateway",
+    "handsfree-head-unit",
+    "off",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileKind {
+    HighQuality,
+    Headset,
+    Off,
+    Other,
+}
+
+impl ProfileKind {
+    fn from_profile(name: &str) -> Self {
+        if name.starts_with("a2dp") {
+            Self::HighQuality
+        } else if name.starts_with("headset") || name.starts_with("handsfree") {
+            Self::Headset
+        } else if name == "off" {
+            Self::Off
+        } else {
+            Self::Other
+        }
+    }
+}
+
+impl Display for ProfileKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::HighQuality => "High quality playback (A2DP)",
+            Self::Headset => "Headset with microphone (HFP/HSP)",
+            Self::Off => "Off",
+            Self::Other => "Other",
+        })
+    }
+}
+
+/// Bluetooth address of the device a card belongs to
+pub fn card_address(card: &AudioCard) -> Option<String> {
+    card.name
+        .strip_prefix(BLUEZ_CARD_PREFIX)
+        .map(|address| address.replace('_', ":"))
+}
+
+pub fn find_device_card(cards: Vec<AudioCard>, address: &str) -> Option<AudioCard> {
+    cards.into_iter().find(|card| {
+        card_address(card).is_some_and(|card_address| card_address.eq_ignore_ascii_case(address))
+    })
+}
+
+/// Codec part of a profile name, None for the default codec of the profile
+pub fn profile_codec(name: &str) -> Option<&str> {
+    let base = PROFILE_BASES.iter().find(|base| name.starts_with(*base))?;
+    name[base.len()..]
+        .strip_prefix('-')
+        .filter(|codec| !codec.is_empty())
+}
+
+/// A profile shown in the codec picker
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodecOption {
+    pub profile: String,
+    label: String,
+}
+
+impl Display for CodecOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+impl From<&AudioCardProfile> for CodecOption {
+    fn from(profile: &AudioCardProfile) -> Self {
+        let label = match (
+            ProfileKind::from_profile(&profile.name),
+            profile_codec(&profile.name),
+        ) {
+            (ProfileKind::Other, _) => profile.description.clone(),
+            (_, Some(codec)) => codec.replace('_', " ").to_uppercase(),
+            (_, None) => "Default".to_string(),
+        };
+        Self {
+            profile: profile.name.clone(),
+            label,
+        }
+    }
+}
+
+pub fn active_kind(card: &AudioCard) -> ProfileKind {
+    ProfileKind::from_profile(&card.active_profile)
+}
+
+/// Kinds that have at least one available profile, in a stable order
+pub fn available_kinds(card: &AudioCard) -> Vec<ProfileKind> {
+    let mut kinds = Vec::new();
+    for kind in [
+        ProfileKind::HighQuality,
+        ProfileKind::Headset,
+        ProfileKind::Other,
+        ProfileKind::Off,
+    ] {
+        if card
+            .profiles
+            .iter()
+            .any(|profile| profile.available && ProfileKind::from_profile(&profile.name) == kind)
+        {
+            kinds.push(kind);
+        }
+    }
+    kinds
+}
+
+/// Available profiles of one kind, one per codec
+pub fn codec_options(card: &AudioCard, kind: ProfileKind) -> Vec<CodecOption> {
+    card.profiles
+        .iter()
+        .filter(|profile| profile.available && ProfileKind::from_profile(&profile.name) == kind)
+        .map(CodecOption::from)
+        .collect()
+}
+
+/// Profile that is activated when switching to another kind
+pub fn default_profile(card: &AudioCard, kind: ProfileKind) -> Option<String> {
+    codec_options(card, kind)
+        .into_iter()
+        .next()
+        .map(|option| option.profile)
+}
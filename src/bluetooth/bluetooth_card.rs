@@ -1,19 +1,36 @@
+use std::{collections::HashMap, fmt::Display};
+
 use iced::{
+    alignment::{Horizontal, Vertical},
     border::Radius,
-    widget::{self, column, container, row, text},
+    widget::{self, canvas, column, container, progress_bar, row, text, text_input},
     Border, Element, Length,
 };
 use oxiced::widgets::oxi_button::{button, ButtonVariant};
+use zbus::zvariant::OwnedObjectPath;
 
 use crate::{
-    components::icons::{icon_widget, Icon},
+    audio::dbus_interface::AudioCard,
+    components::{
+        comborow::{ComboPickerTitle, CustomPickList, PickerVariant},
+        icons::{icon_widget, Icon},
+    },
     utils::rounded_card,
     ReSetMessage,
 };
 
 use super::{
-    bluetooth_impl::BluetoothMsg,
-    dbus_interface::{BluetoothAdapter, BluetoothDevice},
+    adapter_state::AdapterState,
+    agent::{format_passkey, AgentRequest, PendingAgentRequest},
+    audio_profile::{active_kind, available_kinds, codec_options, default_profile, CodecOption},
+    bluetooth_impl::{BluetoothMsg, BluetoothPageId},
+    dbus_interface::{BluetoothAdapter, BluetoothAdapterDetails, BluetoothDevice},
+    device_filter::{display_name, DeviceFilter, DeviceSort, KindFilter, SignalFilter},
+    device_kind::DeviceKind,
+    gatt::{can_notify, can_write, format_value, has_flag, GattInspector, ValueFormat},
+    obex::{format_size, supports_object_push, IncomingTransfer, Transfer, TransferStatus},
+    signal::{signal_icon, RssiGraph, RssiHistory, RSSI_WINDOW},
+    uuid::uuid_label,
 };
 
 #[derive(Clone, Copy)]
@@ -52,56 +69,63 @@ fn create_button<'a>(
     index: usize,
     length: usize,
     value: &BluetoothDevice,
+    battery: Option<u8>,
     variant: BluetoothButtonVariant,
-) -> Option<Element<'a, ReSetMessage>> {
-    let (msg, icon) = match variant {
-        BluetoothButtonVariant::Connect => (
-            BluetoothMsg::ConnectToBluetoothDevice(value.path.clone()),
-            Icon::Bluetooth,
-        ),
-        BluetoothButtonVariant::Disconnect => (
-            BluetoothMsg::DisconnectFromBluetoothDevice(value.path.clone()),
-            Icon::BluetoothConnected,
-        ),
+) -> Element<'a, ReSetMessage> {
+    let msg = match variant {
+        BluetoothButtonVariant::Connect => {
+            BluetoothMsg::ConnectToBluetoothDevice(value.path.clone())
+        }
+        BluetoothButtonVariant::Disconnect => {
+            BluetoothMsg::DisconnectFromBluetoothDevice(value.path.clone())
+        }
     };
-    // TODO beforepr should the empty entries be removed??
-    if value.alias == "" {
-        None
-    } else {
-        Some(
+    button(
+        row!(
+            icon_widget(DeviceKind::from_icon(&value.icon).icon()).width(Length::Shrink),
+            text(display_name(value).to_string()).width(Length::Fill),
+            text(
+                battery
+                    .map(|battery| format!("{}%", battery))
+                    .unwrap_or_default()
+            ),
+            icon_widget(signal_icon(value.rssi)).width(Length::Shrink),
             button(
-                row!(
-                    icon_widget(icon).width(Length::Shrink),
-                    text(value.alias.clone()),
-                )
-                .spacing(10),
-                ButtonVariant::Primary,
+                icon_widget(Icon::ChevronRight).width(Length::Shrink),
+                ButtonVariant::RowEntry
             )
-            .on_press_maybe(if value.conect_in_progress {
-                None
-            } else {
-                Some(ReSetMessage::SubMsgBluetooth(msg))
-            })
-            .style(move |theme, state| {
-                let at = if length == 1 {
-                    RowAt::Only
-                } else if index == 0 {
-                    RowAt::End
-                } else if index == length - 1 {
-                    RowAt::Start
-                } else {
-                    RowAt::Between
-                };
-                row_button_style(oxiced::widgets::oxi_button::row_entry(theme, state), at)
-            })
-            .width(Length::Fill)
-            .into(),
+            .on_press(wrap(BluetoothMsg::SetPageId(BluetoothPageId::Device(
+                value.path.clone()
+            )))),
         )
-    }
+        .spacing(10)
+        .align_y(Vertical::Center),
+        ButtonVariant::Primary,
+    )
+    .on_press_maybe(if value.conect_in_progress {
+        None
+    } else {
+        Some(ReSetMessage::SubMsgBluetooth(msg))
+    })
+    .style(move |theme, state| {
+        let at = if length == 1 {
+            RowAt::Only
+        } else if index == 0 {
+            RowAt::End
+        } else if index == length - 1 {
+            RowAt::Start
+        } else {
+            RowAt::Between
+        };
+        row_button_style(oxiced::widgets::oxi_button::row_entry(theme, state), at)
+    })
+    .width(Length::Fill)
+    .into()
 }
 
 pub fn bluetooth_device_buttons<'a>(
     devices: &Vec<&BluetoothDevice>,
+    batteries: &HashMap<OwnedObjectPath, u8>,
     variant: BluetoothButtonVariant,
 ) -> Element<'a, ReSetMessage> {
     let length = devices.len();
@@ -112,7 +136,10 @@ pub fn bluetooth_device_buttons<'a>(
     let views: Vec<Element<'_, ReSetMessage>> = devices
         .iter()
         .enumerate()
-        .filter_map(|(index, value)| create_button(index, length, value, variant))
+        .map(|(index, value)| {
+            let battery = batteries.get(&value.path).copied();
+            create_button(index, length, value, battery, variant)
+        })
         .collect();
     column!(
         text(title).size(25),
@@ -126,23 +153,89 @@ fn wrap(msg: BluetoothMsg) -> ReSetMessage {
     ReSetMessage::SubMsgBluetooth(msg)
 }
 
+/// Discoverable timeouts offered in the adapter settings, 0 is forever
+const DISCOVERABLE_TIMEOUTS: [u32; 5] = [60, 180, 300, 600, 0];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DiscoverableTimeout(u32);
+
+impl Display for DiscoverableTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            0 => f.write_str("Forever"),
+            60 => f.write_str("1 minute"),
+            seconds if seconds % 60 == 0 => write!(f, "{} minutes", seconds / 60),
+            seconds => write!(f, "{} seconds", seconds),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AdapterOption {
+    path: OwnedObjectPath,
+    alias: String,
+}
+
+impl Display for AdapterOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.alias)
+    }
+}
+
+impl From<&BluetoothAdapter> for AdapterOption {
+    fn from(adapter: &BluetoothAdapter) -> Self {
+        Self {
+            path: adapter.path.clone(),
+            alias: adapter.alias.clone(),
+        }
+    }
+}
+
 fn card_view<'a>(
-    index: usize,
-    is_default: bool,
     adapter: &BluetoothAdapter,
+    details: Option<&BluetoothAdapterDetails>,
+    alias_edit: Option<&str>,
 ) -> Element<'a, ReSetMessage> {
-    let default_index = if is_default { Some(index) } else { None };
     let path = adapter.path.clone();
     let path1 = adapter.path.clone();
     let path2 = adapter.path.clone();
     let path3 = adapter.path.clone();
+    let path4 = adapter.path.clone();
+    let alias = alias_edit.unwrap_or(&adapter.alias).to_string();
+    // unchanged or empty names are not sent
+    let rename =
+        (alias_edit.is_some() && !alias.trim().is_empty() && alias != adapter.alias).then(|| {
+            wrap(BluetoothMsg::SetBluetoothAdapterAlias(
+                adapter.path.clone(),
+                alias.clone(),
+            ))
+        });
+    let unknown = || "Unknown".to_string();
+    let address = details.map_or_else(unknown, |details| details.address.clone());
+    let manufacturer = details
+        .map(|details| details.manufacturer.clone())
+        .filter(|manufacturer| !manufacturer.is_empty())
+        .unwrap_or_else(unknown);
+    let roles = details
+        .map(|details| details.roles.join(", "))
+        .filter(|roles| !roles.is_empty())
+        .unwrap_or_else(unknown);
     let col = column!(
         row!(
-            text(adapter.alias.clone()).width(Length::Fill).size(25),
-            oxiced::widgets::oxi_radio::radio("", index, default_index, move |_| wrap(
-                BluetoothMsg::SetBluetoothAdapter(path.clone())
-            ))
-        ),
+            text_input("Name", &alias)
+                .on_input(move |alias| wrap(BluetoothMsg::EditBluetoothAdapterAlias(
+                    path.clone(),
+                    alias
+                )))
+                .on_submit_maybe(rename.clone())
+                .size(20),
+            button(text("Rename"), ButtonVariant::Primary).on_press_maybe(rename),
+        )
+        .spacing(10)
+        .align_y(Vertical::Center),
+        info_row("Address", address),
+        info_row("Manufacturer", manufacturer),
+        info_row("Supported modes", roles),
         row!(
             text("Powered").width(Length::Fill),
             oxiced::widgets::oxi_toggler::toggler(adapter.powered).on_toggle(move |value| wrap(
@@ -158,6 +251,18 @@ fn card_view<'a>(
                 ))
             })
         ),
+        CustomPickList::new(
+            PickerVariant::ComboPicker(ComboPickerTitle::new(
+                "Discoverable timeout",
+                None::<String>
+            )),
+            DISCOVERABLE_TIMEOUTS.map(DiscoverableTimeout).to_vec(),
+            details.map(|details| DiscoverableTimeout(details.discoverable_timeout)),
+            move |timeout| wrap(BluetoothMsg::SetBluetoothAdapterDiscoverableTimeout(
+                path4.clone(),
+                timeout.0
+            )),
+        ),
         row!(
             text("Pairable").width(Length::Fill),
             oxiced::widgets::oxi_toggler::toggler(adapter.pairable).on_toggle(move |value| wrap(
@@ -172,17 +277,718 @@ fn card_view<'a>(
 }
 
 pub fn bluetooth_adapter_view<'a>(
-    default_adapter: &BluetoothAdapter,
+    default_adapter: Option<&BluetoothAdapter>,
     adapters: &Vec<&BluetoothAdapter>,
+    details: &HashMap<OwnedObjectPath, BluetoothAdapterDetails>,
+    alias_edit: &Option<(OwnedObjectPath, String)>,
 ) -> Element<'a, ReSetMessage> {
-    // TODO add adapter picker
+    let options: Vec<AdapterOption> = adapters
+        .iter()
+        .map(|adapter| AdapterOption::from(*adapter))
+        .collect();
+    let picker = CustomPickList::new(
+        PickerVariant::ComboPicker(ComboPickerTitle::new(
+            "Current adapter",
+            Some("Used for scanning and connecting"),
+        )),
+        options,
+        default_adapter.map(AdapterOption::from),
+        |adapter| wrap(BluetoothMsg::SetBluetoothAdapter(adapter.path)),
+    );
     let views: Vec<Element<'a, ReSetMessage>> = adapters
         .iter()
-        .enumerate()
-        .map(|(index, adapter)| {
-            let is_default = adapter.path == default_adapter.path;
-            card_view(index, is_default, adapter)
+        .map(|adapter| {
+            let edit = match alias_edit {
+                Some((path, alias)) if *path == adapter.path => Some(alias.as_str()),
+                _ => None,
+            };
+            card_view(adapter, details.get(&adapter.path), edit)
         })
         .collect();
-    iced::widget::Column::with_children(views).into()
+    column!(picker).extend(views).spacing(20).into()
+}
+
+/// Empty state shown instead of the pages that need a usable adapter
+pub fn adapter_state_view<'a>(
+    state: AdapterState,
+    adapter: Option<&BluetoothAdapter>,
+) -> Element<'a, ReSetMessage> {
+    let action = adapter.and_then(|adapter| match state {
+        AdapterState::Blocked => Some(button(text("Unblock"), ButtonVariant::Primary).on_press(
+            wrap(BluetoothMsg::UnblockBluetoothAdapter(adapter.path.clone())),
+        )),
+        AdapterState::PoweredOff => Some(button(text("Turn on"), ButtonVariant::Primary).on_press(
+            wrap(BluetoothMsg::SetBluetoothAdapterEnabled(
+                adapter.path.clone(),
+                true,
+            )),
+        )),
+        _ => None,
+    });
+    let settings = adapter.is_some().then(|| {
+        button(text("Adapter settings"), ButtonVariant::RowEntry)
+            .on_press(wrap(BluetoothMsg::SetPageId(BluetoothPageId::Adapter)))
+    });
+    container(
+        column!(
+            icon_widget(Icon::BluetoothDisabled)
+                .width(Length::Shrink)
+                .height(64),
+            text(state.title()).size(25),
+            text(state.description()),
+            row![].push_maybe(action).push_maybe(settings).spacing(10),
+        )
+        .spacing(20)
+        .align_x(Horizontal::Center),
+    )
+    .padding(40)
+    .center_x(Length::Fill)
+    .into()
+}
+
+fn passkey_text<'a>(passkey: u32) -> Element<'a, ReSetMessage> {
+    text(format_passkey(passkey))
+        .size(40)
+        .width(Length::Fill)
+        .align_x(Horizontal::Center)
+        .into()
+}
+
+pub fn agent_dialog_view<'a>(
+    pending: &'a PendingAgentRequest,
+    device_name: String,
+) -> Element<'a, ReSetMessage> {
+    let reject = |title| {
+        button(text(title), ButtonVariant::RowEntry).on_press(wrap(BluetoothMsg::AgentReply(false)))
+    };
+    let accept = |title, enabled: bool| {
+        button(text(title), ButtonVariant::Primary)
+            .on_press_maybe(enabled.then(|| wrap(BluetoothMsg::AgentReply(true))))
+    };
+    let (description, content, actions): (String, Option<Element<ReSetMessage>>, _) =
+        match &pending.request {
+            AgentRequest::PinCode(_) => {
+                let valid = pending.valid_pin();
+                (
+                    format!("Enter the PIN code for {}", device_name),
+                    Some(
+                        text_input("PIN code", &pending.pin)
+                            .on_input(|pin| wrap(BluetoothMsg::AgentPinChanged(pin)))
+                            .on_submit_maybe(valid.then(|| wrap(BluetoothMsg::AgentReply(true))))
+                            .into(),
+                    ),
+                    row!(reject("Cancel"), accept("Pair", valid)),
+                )
+            }
+            AgentRequest::DisplayPasskey(_, passkey, entered) => (
+                format!(
+                    "Type this passkey on {} and confirm with enter ({} of 6 digits entered)",
+                    device_name, entered
+                ),
+                Some(passkey_text(*passkey)),
+                row!(reject("Cancel")),
+            ),
+            AgentRequest::Confirmation(_, passkey) => (
+                format!("Confirm that {} shows the same passkey", device_name),
+                Some(passkey_text(*passkey)),
+                row!(reject("Cancel"), accept("Confirm", true)),
+            ),
+            AgentRequest::AuthorizeService(_, uuid) => (
+                format!(
+                    "{} wants to use the service {}",
+                    device_name,
+                    uuid_label(uuid)
+                ),
+                None,
+                row!(reject("Reject"), accept("Allow", true)),
+            ),
+        };
+    container(
+        column!(text("Pairing request").size(25), text(description))
+            .push_maybe(content)
+            .push(actions.spacing(10))
+            .spacing(20)
+            .padding(20)
+            .width(400),
+    )
+    .style(rounded_card)
+    .into()
+}
+
+fn info_row<'a>(title: &'a str, value: String) -> Element<'a, ReSetMessage> {
+    row!(text(title).width(Length::Fill), text(value)).into()
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "Yes" } else { "No" }.to_string()
+}
+
+/// Profile and codec of the audio card that belongs to a device
+fn device_audio_view<'a>(card: &AudioCard) -> Element<'a, ReSetMessage> {
+    let card_index = card.index;
+    let kind = active_kind(card);
+    let profile_card = card.clone();
+    let profile_picker = CustomPickList::new(
+        PickerVariant::ComboPicker(ComboPickerTitle::new("Profile", None::<String>)),
+        available_kinds(card),
+        Some(kind),
+        move |kind| match default_profile(&profile_card, kind) {
+            Some(profile) => wrap(BluetoothMsg::SetDeviceAudioProfile(card_index, profile)),
+            None => wrap(BluetoothMsg::SetDeviceAudioProfile(
+                card_index,
+                profile_card.active_profile.clone(),
+            )),
+        },
+    );
+    let codecs = codec_options(card, kind);
+    let selected = codecs
+        .iter()
+        .find(|codec| codec.profile == card.active_profile)
+        .cloned();
+    // a single option has nothing to choose from
+    let codec_picker = (codecs.len() > 1).then(|| {
+        CustomPickList::new(
+            PickerVariant::ComboPicker(ComboPickerTitle::new("Codec", None::<String>)),
+            codecs,
+            selected,
+            move |codec: CodecOption| {
+                wrap(BluetoothMsg::SetDeviceAudioProfile(
+                    card_index,
+                    codec.profile,
+                ))
+            },
+        )
+    });
+    column!(text("Audio").size(20), profile_picker)
+        .push_maybe(codec_picker)
+        .spacing(10)
+        .padding(10)
+        .width(Length::Fill)
+        .into()
+}
+
+pub fn device_detail_view<'a>(
+    device: &BluetoothDevice,
+    battery: Option<u8>,
+    profiles: &[String],
+    audio_card: Option<&AudioCard>,
+    transfer_running: bool,
+    favorite: bool,
+    rssi_history: Option<&'a RssiHistory>,
+) -> Element<'a, ReSetMessage> {
+    let path = device.path.clone();
+    let signal = if device.rssi == 0 {
+        "Not in range".to_string()
+    } else {
+        format!("{} dBm", device.rssi)
+    };
+    let info = column!(
+        row!(
+            icon_widget(DeviceKind::from_icon(&device.icon).icon())
+                .width(Length::Shrink)
+                .height(32),
+            text(display_name(device).to_string()).size(25)
+        )
+        .spacing(10)
+        .align_y(Vertical::Center),
+        info_row("Address", device.address.clone()),
+        info_row(
+            "Type",
+            DeviceKind::from_icon(&device.icon).label().to_string()
+        ),
+        info_row("Signal", signal),
+        info_row("Connected", yes_no(device.connected)),
+        info_row("Paired", yes_no(device.paired)),
+        info_row("Bonded", yes_no(device.bonded)),
+        info_row("Trusted", yes_no(device.trusted)),
+        info_row("Blocked", yes_no(device.blocked)),
+    )
+    .push_maybe(battery.map(|battery| info_row("Battery", format!("{}%", battery))))
+    .spacing(10)
+    .padding(10)
+    .width(Length::Fill);
+
+    let graph: Element<'a, ReSetMessage> = match rssi_history {
+        Some(history) if !history.is_empty() => canvas(RssiGraph { history })
+            .width(Length::Fill)
+            .height(120)
+            .into(),
+        _ => text("No signal measured yet, signals are reported while scanning").into(),
+    };
+    let signal = column!(
+        text(format!(
+            "Signal strength (last {} seconds)",
+            RSSI_WINDOW.as_secs()
+        ))
+        .size(20),
+        graph
+    )
+    .spacing(10)
+    .padding(10)
+    .width(Length::Fill);
+
+    let object_push = supports_object_push(profiles);
+    let profile_rows: Vec<Element<'a, ReSetMessage>> = if profiles.is_empty() {
+        vec![text("No profiles known").into()]
+    } else {
+        profiles
+            .iter()
+            .map(|uuid| text(uuid_label(uuid)).into())
+            .collect()
+    };
+    let profiles = column!(text("Profiles").size(20))
+        .extend(profile_rows)
+        .spacing(10)
+        .padding(10)
+        .width(Length::Fill);
+
+    let connection = if device.connected {
+        button(text("Disconnect"), ButtonVariant::Primary).on_press_maybe(
+            (!device.conect_in_progress)
+                .then(|| wrap(BluetoothMsg::DisconnectFromBluetoothDevice(path.clone()))),
+        )
+    } else {
+        button(text("Connect"), ButtonVariant::Primary).on_press_maybe(
+            (!device.conect_in_progress)
+                .then(|| wrap(BluetoothMsg::ConnectToBluetoothDevice(path.clone()))),
+        )
+    };
+    let send_file = (device.connected && object_push).then(|| {
+        button(text("Send file"), ButtonVariant::RowEntry).on_press_maybe(
+            (!transfer_running).then(|| wrap(BluetoothMsg::ChooseFileToSend(path.clone()))),
+        )
+    });
+    let actions = row!(connection)
+        .push_maybe(send_file)
+        .push(
+            button(
+                text(if device.trusted { "Untrust" } else { "Trust" }),
+                ButtonVariant::RowEntry,
+            )
+            .on_press(wrap(BluetoothMsg::SetBluetoothDeviceTrusted(
+                path.clone(),
+                !device.trusted,
+            ))),
+        )
+        .push(
+            button(
+                text(if device.blocked { "Unblock" } else { "Block" }),
+                ButtonVariant::RowEntry,
+            )
+            .on_press(wrap(BluetoothMsg::SetBluetoothDeviceBlocked(
+                path.clone(),
+                !device.blocked,
+            ))),
+        )
+        .push(
+            button(
+                text(if favorite {
+                    "Remove favorite"
+                } else {
+                    "Add favorite"
+                }),
+                ButtonVariant::RowEntry,
+            )
+            .on_press(wrap(BluetoothMsg::ToggleFavorite(device.address.clone()))),
+        )
+        .push(
+            button(text("Forget"), ButtonVariant::RowEntry)
+                .on_press(wrap(BluetoothMsg::RemoveDevicePairing(path))),
+        )
+        .spacing(10);
+
+    // only connected devices expose their GATT database
+    let inspector = device.connected.then(|| {
+        button(
+            row!(
+                text("GATT inspector").width(Length::Fill).size(20),
+                icon_widget(Icon::ChevronRight).width(Length::Shrink)
+            )
+            .width(Length::Fill),
+            ButtonVariant::RowEntry,
+        )
+        .on_press(wrap(BluetoothMsg::SetPageId(BluetoothPageId::Gatt(
+            device.path.clone(),
+        ))))
+        .width(Length::Fill)
+    });
+
+    column!(container(info).style(rounded_card))
+        .push_maybe(audio_card.map(|card| container(device_audio_view(card)).style(rounded_card)))
+        .push(container(signal).style(rounded_card))
+        .push(container(profiles).style(rounded_card))
+        .push_maybe(inspector)
+        .push(actions)
+        .spacing(20)
+        .into()
+}
+
+fn uuid_row<'a>(uuid: &str) -> Element<'a, ReSetMessage> {
+    column!(text(uuid_label(uuid)), text(uuid.to_string()).size(12)).into()
+}
+
+/// Value, read, write and notify controls of the selected characteristic
+fn gatt_characteristic_view<'a>(gatt: &GattInspector) -> Element<'a, ReSetMessage> {
+    let characteristic = match gatt.selected_characteristic() {
+        Some(characteristic) => characteristic,
+        None => return text("Select a characteristic").into(),
+    };
+    let path = characteristic.path.clone();
+    let value = match gatt.values.get(&path) {
+        Some(value) => format_value(value, gatt.format),
+        None => "Not read yet".to_string(),
+    };
+    let format_picker = CustomPickList::new(
+        PickerVariant::ComboPicker(ComboPickerTitle::new("Format", None::<String>)),
+        ValueFormat::ALL.to_vec(),
+        Some(gatt.format),
+        |format| wrap(BluetoothMsg::SetGattValueFormat(format)),
+    );
+    let mut actions = row!().spacing(10);
+    if has_flag(characteristic, "read") {
+        actions = actions.push(
+            button(text("Read"), ButtonVariant::Primary)
+                .on_press(wrap(BluetoothMsg::ReadGattCharacteristic(path.clone()))),
+        );
+    }
+    if can_notify(characteristic) {
+        let subscribed = gatt.subscribed.contains(&path);
+        actions = actions.push(
+            button(
+                text(if subscribed {
+                    "Unsubscribe"
+                } else {
+                    "Subscribe"
+                }),
+                ButtonVariant::RowEntry,
+            )
+            .on_press(wrap(BluetoothMsg::ToggleGattNotify(path.clone()))),
+        );
+    }
+    let write = can_write(characteristic).then(|| {
+        let submit = wrap(BluetoothMsg::WriteGattCharacteristic(path.clone()));
+        row!(
+            text_input("Value to write", &gatt.write_input)
+                .on_input(|input| wrap(BluetoothMsg::EditGattWrite(input)))
+                .on_submit(submit.clone()),
+            button(text("Write"), ButtonVariant::Primary).on_press(submit),
+        )
+        .spacing(10)
+        .align_y(Vertical::Center)
+    });
+    column!(
+        uuid_row(&characteristic.uuid),
+        info_row("Flags", characteristic.flags.join(", ")),
+        format_picker,
+        text(value).size(20),
+        actions,
+    )
+    .push_maybe(write)
+    .push_maybe(gatt.error.clone().map(text))
+    .spacing(10)
+    .padding(10)
+    .width(Length::Fill)
+    .into()
+}
+
+pub fn gatt_view<'a>(gatt: &GattInspector, device_name: String) -> Element<'a, ReSetMessage> {
+    let services: Vec<Element<'a, ReSetMessage>> = gatt
+        .services
+        .iter()
+        .map(|service| {
+            let characteristics: Vec<Element<'a, ReSetMessage>> = service
+                .characteristics
+                .iter()
+                .map(|characteristic| {
+                    button(
+                        row!(
+                            column!(uuid_row(&characteristic.uuid)).width(Length::Fill),
+                            text(characteristic.flags.join(", ")).size(12)
+                        )
+                        .spacing(10)
+                        .align_y(Vertical::Center),
+                        ButtonVariant::RowEntry,
+                    )
+                    .on_press(wrap(BluetoothMsg::SelectGattCharacteristic(
+                        characteristic.path.clone(),
+                    )))
+                    .width(Length::Fill)
+                    .into()
+                })
+                .collect();
+            let title = if service.primary {
+                "Primary service"
+            } else {
+                "Secondary service"
+            };
+            container(
+                column!(text(title).size(12), uuid_row(&service.uuid))
+                    .extend(characteristics)
+                    .spacing(10)
+                    .padding(10)
+                    .width(Length::Fill),
+            )
+            .style(rounded_card)
+            .into()
+        })
+        .collect();
+    let services: Element<'a, ReSetMessage> = if services.is_empty() {
+        text(
+            gatt.error
+                .clone()
+                .unwrap_or_else(|| "The device has no GATT services".to_string()),
+        )
+        .into()
+    } else {
+        iced::widget::Column::with_children(services)
+            .spacing(10)
+            .into()
+    };
+    let log: Vec<Element<'a, ReSetMessage>> = gatt
+        .log
+        .iter()
+        .rev()
+        .map(|entry| {
+            let name = match gatt.characteristic(&entry.characteristic) {
+                Some(characteristic) => uuid_label(&characteristic.uuid),
+                None => entry.characteristic.to_string(),
+            };
+            row!(
+                text(format!("{:.1}s", entry.time)).width(60),
+                text(name).width(Length::FillPortion(1)),
+                text(format_value(&entry.value, gatt.format)).width(Length::FillPortion(2)),
+            )
+            .spacing(10)
+            .into()
+        })
+        .collect();
+    column!(
+        text(format!("GATT services of {}", device_name)).size(25),
+        services,
+        container(gatt_characteristic_view(gatt)).style(rounded_card),
+        row!(
+            text("Notifications").size(20).width(Length::Fill),
+            button(text("Clear"), ButtonVariant::RowEntry)
+                .on_press_maybe((!gatt.log.is_empty()).then(|| wrap(BluetoothMsg::ClearGattLog)))
+        )
+        .align_y(Vertical::Center),
+        container(
+            iced::widget::Column::with_children(log)
+                .spacing(5)
+                .padding(10)
+                .width(Length::Fill)
+        )
+        .style(rounded_card),
+    )
+    .spacing(20)
+    .into()
+}
+
+/// Favorites in priority order with their address and name if the device is known
+pub fn favorites_view<'a>(favorites: Vec<(String, Option<String>)>) -> Element<'a, ReSetMessage> {
+    let length = favorites.len();
+    let rows: Vec<Element<'a, ReSetMessage>> = if favorites.is_empty() {
+        vec![text("Favorites are connected automatically, add them from the device details").into()]
+    } else {
+        favorites
+            .into_iter()
+            .enumerate()
+            .map(|(index, (address, name))| {
+                row!(
+                    text(format!("{}.", index + 1)),
+                    text(name.unwrap_or_else(|| address.clone())).width(Length::Fill),
+                    button(text("Up"), ButtonVariant::RowEntry).on_press_maybe(
+                        (index > 0).then(|| wrap(BluetoothMsg::MoveFavorite(index, index - 1)))
+                    ),
+                    button(text("Down"), ButtonVariant::RowEntry).on_press_maybe(
+                        (index + 1 < length)
+                            .then(|| wrap(BluetoothMsg::MoveFavorite(index, index + 1)))
+                    ),
+                    button(text("Remove"), ButtonVariant::RowEntry)
+                        .on_press(wrap(BluetoothMsg::ToggleFavorite(address))),
+                )
+                .spacing(10)
+                .align_y(Vertical::Center)
+                .into()
+            })
+            .collect()
+    };
+    column!(text("Favorites").size(25))
+        .push(
+            container(
+                iced::widget::Column::with_children(rows)
+                    .spacing(10)
+                    .padding(10)
+                    .width(Length::Fill),
+            )
+            .style(rounded_card),
+        )
+        .spacing(20)
+        .into()
+}
+
+/// Progress of the current file transfer
+pub fn transfer_view<'a>(transfer: &Transfer, device_name: String) -> Element<'a, ReSetMessage> {
+    let title = if transfer.incoming {
+        format!("Receiving {} from {}", transfer.name, device_name)
+    } else {
+        format!("Sending {} to {}", transfer.name, device_name)
+    };
+    let status = match transfer.status {
+        TransferStatus::Queued => "Waiting for the device".to_string(),
+        TransferStatus::Suspended => "Paused".to_string(),
+        TransferStatus::Complete => "Done".to_string(),
+        TransferStatus::Error => "Failed or canceled".to_string(),
+        TransferStatus::Active if transfer.size == 0 => format_size(transfer.transferred),
+        TransferStatus::Active => format!(
+            "{} of {}",
+            format_size(transfer.transferred),
+            format_size(transfer.size)
+        ),
+    };
+    let action = if transfer.status.is_finished() {
+        button(text("Dismiss"), ButtonVariant::RowEntry)
+            .on_press(wrap(BluetoothMsg::DismissTransfer))
+    } else {
+        button(text("Cancel"), ButtonVariant::RowEntry).on_press(wrap(BluetoothMsg::CancelTransfer))
+    };
+    container(
+        column!(
+            row!(text(title).width(Length::Fill), action)
+                .spacing(10)
+                .align_y(Vertical::Center),
+            progress_bar(0.0..=100.0, transfer.progress()).height(10),
+            text(status),
+        )
+        .spacing(10)
+        .padding(10)
+        .width(Length::Fill),
+    )
+    .style(rounded_card)
+    .into()
+}
+
+pub fn transfer_dialog_view<'a>(
+    incoming: &IncomingTransfer,
+    device_name: String,
+) -> Element<'a, ReSetMessage> {
+    let size = if incoming.size == 0 {
+        String::new()
+    } else {
+        format!(" ({})", format_size(incoming.size))
+    };
+    container(
+        column!(
+            text("Incoming file").size(25),
+            text(format!(
+                "{} wants to send {}{}",
+                device_name, incoming.name, size
+            )),
+            row!(
+                button(text("Reject"), ButtonVariant::RowEntry)
+                    .on_press(wrap(BluetoothMsg::AnswerTransfer(false))),
+                button(text("Accept"), ButtonVariant::Primary)
+                    .on_press(wrap(BluetoothMsg::AnswerTransfer(true))),
+            )
+            .spacing(10)
+        )
+        .spacing(20)
+        .padding(20)
+        .width(400),
+    )
+    .style(rounded_card)
+    .into()
+}
+
+/// Settings for files sent by other devices
+pub fn receive_files_view<'a>(enabled: bool, folder: &str) -> Element<'a, ReSetMessage> {
+    let col = column!(
+        text("File transfer").size(20),
+        row!(
+            text("Receive files").width(Length::Fill),
+            oxiced::widgets::oxi_toggler::toggler(enabled)
+                .on_toggle(|value| wrap(BluetoothMsg::SetReceiveFiles(value)))
+        ),
+        row!(
+            column!(text("Folder"), text(folder.to_string()).size(12)).width(Length::Fill),
+            button(text("Change"), ButtonVariant::RowEntry)
+                .on_press(wrap(BluetoothMsg::ChooseReceiveFolder))
+        )
+        .spacing(10)
+        .align_y(Vertical::Center)
+    )
+    .spacing(10)
+    .padding(10)
+    .width(Length::Fill);
+    container(col).style(rounded_card).into()
+}
+
+/// Copies the filter with one change applied
+fn filter_msg(filter: &DeviceFilter, change: impl FnOnce(&mut DeviceFilter)) -> ReSetMessage {
+    let mut filter = filter.clone();
+    change(&mut filter);
+    wrap(BluetoothMsg::SetDeviceFilter(filter))
+}
+
+pub fn device_filter_view<'a>(filter: &DeviceFilter) -> Element<'a, ReSetMessage> {
+    let search = {
+        let filter = filter.clone();
+        text_input("Search devices", &filter.search.clone())
+            .on_input(move |search| filter_msg(&filter, |filter| filter.search = search))
+    };
+    let hide_unnamed = {
+        let filter = filter.clone();
+        oxiced::widgets::oxi_toggler::toggler(filter.hide_unnamed)
+            .on_toggle(move |value| filter_msg(&filter, |filter| filter.hide_unnamed = value))
+    };
+    let only_paired = {
+        let filter = filter.clone();
+        oxiced::widgets::oxi_toggler::toggler(filter.only_paired)
+            .on_toggle(move |value| filter_msg(&filter, |filter| filter.only_paired = value))
+    };
+    let kind = {
+        let filter = filter.clone();
+        CustomPickList::new(
+            PickerVariant::ComboPicker(ComboPickerTitle::new("Type", None::<String>)),
+            KindFilter::options(),
+            Some(filter.kind),
+            move |kind| filter_msg(&filter, |filter| filter.kind = kind),
+        )
+    };
+    let signal = {
+        let filter = filter.clone();
+        CustomPickList::new(
+            PickerVariant::ComboPicker(ComboPickerTitle::new("Minimum signal", None::<String>)),
+            SignalFilter::ALL.to_vec(),
+            Some(filter.signal),
+            move |signal| filter_msg(&filter, |filter| filter.signal = signal),
+        )
+    };
+    let sort = {
+        let filter = filter.clone();
+        CustomPickList::new(
+            PickerVariant::ComboPicker(ComboPickerTitle::new("Sort by", None::<String>)),
+            DeviceSort::ALL.to_vec(),
+            Some(filter.sort),
+            move |sort| filter_msg(&filter, |filter| filter.sort = sort),
+        )
+    };
+    container(
+        column!(
+            search,
+            row!(
+                text("Hide unnamed devices").width(Length::Fill),
+                hide_unnamed
+            ),
+            row!(text("Only paired devices").width(Length::Fill), only_paired),
+            kind,
+            signal,
+            sort,
+        )
+        .spacing(10)
+        .padding(10)
+        .width(Length::Fill),
+    )
+    .style(rounded_card)
+    .into()
 }
@@ -1,40 +1,117 @@
 use std::{
-    collections::HashMap,
-    sync::{atomic::AtomicU8, Arc},
-    thread,
-    time::Duration,
+    collections::{HashMap, HashSet},
+    future::Future,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use iced::{
-    futures::{channel::mpsc::Sender, SinkExt, StreamExt},
-    widget::{column, row, text},
-    Element, Length, Task,
+    alignment::Vertical,
+    futures::{
+        channel::mpsc::Sender,
+        future::{select, Either},
+        SinkExt, StreamExt,
+    },
+    widget::{column, container, row, text},
+    Element, Length, Padding, Task,
 };
 use oxiced::widgets::oxi_button::ButtonVariant;
+use re_set_lib::LOG;
 use zbus::{zvariant::OwnedObjectPath, Connection, Proxy};
 
 use crate::{
+    audio::dbus_interface::{AudioCard, AudioDbusProxy},
     components::{
+        comborow::{ComboPickerTitle, CustomPickList, PickerVariant},
         easing::STANDARD,
+        error_banner::{error_banner, error_banner_with_action},
         icons::{icon_widget, Icon},
         loading_spinner::Circular,
     },
-    utils::TToError,
-    PageId, ReSetMessage,
+    config::{BluetoothConfig, Config},
+    portal::choose_file,
+    utils::{daemon_error_message, ignore, TToError},
+    ReSetMessage,
 };
 
 use super::{
-    bluetooth_card::{bluetooth_adapter_view, bluetooth_device_buttons, BluetoothButtonVariant},
-    dbus_interface::{BluetoothAdapter, BluetoothDbusProxy, BluetoothDevice, TPath},
+    adapter_state::AdapterState,
+    agent::{AgentRequest, PendingAgentRequest, AGENT_TIMEOUT},
+    audio_profile::find_device_card,
+    bluetooth_card::{
+        adapter_state_view, agent_dialog_view, bluetooth_adapter_view, bluetooth_device_buttons,
+        device_detail_view, device_filter_view, favorites_view, gatt_view, receive_files_view,
+        transfer_dialog_view, transfer_view, BluetoothButtonVariant,
+    },
+    connection_error::{
+        connection_error_reason, connection_result, ConnectionError, CONNECT_TIMEOUT,
+        RECONNECT_DELAY,
+    },
+    dbus_interface::{
+        BluetoothAdapter, BluetoothAdapterDetails, BluetoothDbusProxy, BluetoothDevice,
+        GattService, TPath, NO_ADAPTER_ERROR,
+    },
+    device_filter::{display_name, DeviceFilter},
+    gatt::{parse_value, GattInspector, ValueFormat},
+    obex::{IncomingTransfer, Transfer, TransferStatus},
+    signal::RssiHistory,
 };
 
-pub struct BluetoothModel<'a> {
-    proxy: Arc<BluetoothDbusProxy<'a>>,
-    current_adapter: BluetoothAdapter,
+pub struct BluetoothModel {
+    /// Used for the file chooser portal
+    connection: Connection,
+    proxy: Arc<BluetoothDbusProxy<'static>>,
+    audio_proxy: Arc<AudioDbusProxy<'static>>,
+    /// None on systems without Bluetooth hardware
+    current_adapter: Option<BluetoothAdapter>,
     adapters: HashMap<zbus::zvariant::OwnedObjectPath, BluetoothAdapter>,
+    /// Details of the adapters, missing if the daemon does not provide them
+    adapter_details: HashMap<zbus::zvariant::OwnedObjectPath, BluetoothAdapterDetails>,
     devices: HashMap<zbus::zvariant::OwnedObjectPath, BluetoothDevice>,
     page_id: BluetoothPageId,
+    /// Alias of an adapter while it is edited
+    alias_edit: Option<(OwnedObjectPath, String)>,
     is_scanning: bool,
+    /// Seconds until the running scan is stopped
+    scan_remaining: u32,
+    /// Used to ignore ticks of scans that were already stopped
+    scan_id: u32,
+    agent_request: Option<PendingAgentRequest>,
+    agent_request_count: u32,
+    /// Profiles of the device shown in the detail page
+    device_profiles: Vec<String>,
+    /// Audio card of the device shown in the detail page
+    device_card: Option<AudioCard>,
+    /// Services of the device shown in the GATT inspector
+    gatt: Option<GattInspector>,
+    config: BluetoothConfig,
+    /// Devices that were already warned about, until they are charged again
+    low_battery: HashSet<OwnedObjectPath>,
+    /// Percentages of org.bluez.Battery1, only known once the daemon reported a change
+    batteries: HashMap<OwnedObjectPath, u8>,
+    battery_warning: Option<String>,
+    device_filter: DeviceFilter,
+    rssi_history: HashMap<OwnedObjectPath, RssiHistory>,
+    connection_error: Option<ConnectionError>,
+    /// Automatic reconnect attempts per device since the last manual one
+    reconnect_attempts: HashMap<OwnedObjectPath, u32>,
+    /// Favorites waiting to be connected, highest priority first
+    auto_connect_queue: Vec<OwnedObjectPath>,
+    /// Favorite that is currently connected automatically
+    auto_connecting: Option<OwnedObjectPath>,
+    /// Id of the running auto connect timer, older timers stop
+    auto_connect_id: u32,
+    /// Periodic auto connect attempts per favorite since it was last seen or connected
+    auto_connect_attempts: HashMap<OwnedObjectPath, u32>,
+    /// Favorites disconnected by hand, skipped by auto connect until the
+    /// adapter is powered on again or the device reappears
+    manually_disconnected: HashSet<OwnedObjectPath>,
+    /// Last file transfer, kept until dismissed once finished
+    transfer: Option<Transfer>,
+    incoming_transfer: Option<IncomingTransfer>,
+    /// The listener and auto connect were started on the first visit of the page
+    started: bool,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -42,69 +119,281 @@ pub enum BluetoothPageId {
     #[default]
     Devices,
     Adapter,
+    Device(zbus::zvariant::OwnedObjectPath),
+    /// GATT inspector of a Bluetooth LE device
+    Gatt(zbus::zvariant::OwnedObjectPath),
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub enum BluetoothMsg {
-    GG,
-    #[default]
-    GetBluetoothAdapters,
+    /// The listener is never stopped, agent prompts, incoming transfers and
+    /// favorites that reappear need it while another page is shown
     StartBluetoothListener,
-    StopBluetoothListener, // TODO use when moving away from this page
+    /// The Bluetooth page became visible
+    EnterPage,
+    /// The Bluetooth page was left
+    LeavePage,
     StartBluetoothScan,
+    /// The daemon started a discovery
+    ScanStarted,
     StopBluetoothScan,
+    ScanTick(u32),
+    /// Periodic attempt to connect paired favorites
+    AutoConnectTick(u32),
+    SetScanDuration(u32),
     SetBluetoothAdapter(zbus::zvariant::OwnedObjectPath),
+    /// The adapter the daemon uses from now on
+    ReceiveCurrentAdapter(BluetoothAdapter),
+    /// An adapter was plugged in or changed
+    AddBluetoothAdapter(BluetoothAdapter),
+    /// Address, manufacturer and rfkill state, fetched apart from the adapter
+    ReceiveAdapterDetails(zbus::zvariant::OwnedObjectPath, BluetoothAdapterDetails),
+    RemoveBluetoothAdapter(zbus::zvariant::OwnedObjectPath),
+    /// Lifts the rfkill block and powers the adapter on
+    UnblockBluetoothAdapter(zbus::zvariant::OwnedObjectPath),
     SetBluetoothAdapterEnabled(zbus::zvariant::OwnedObjectPath, bool),
     SetBluetoothAdapterDiscoverability(zbus::zvariant::OwnedObjectPath, bool),
     SetBluetoothAdapterPairability(zbus::zvariant::OwnedObjectPath, bool),
+    /// Local edit of the alias, only sent once confirmed
+    EditBluetoothAdapterAlias(zbus::zvariant::OwnedObjectPath, String),
+    SetBluetoothAdapterAlias(zbus::zvariant::OwnedObjectPath, String),
+    SetBluetoothAdapterDiscoverableTimeout(zbus::zvariant::OwnedObjectPath, u32),
     ConnectToBluetoothDevice(zbus::zvariant::OwnedObjectPath),
     DisconnectFromBluetoothDevice(zbus::zvariant::OwnedObjectPath),
-    RemoveDevicePairing(zbus::zvariant::OwnedObjectPath), // TODO should this even be done?
+    /// Device, whether it was a connect, and the reason on failure
+    ConnectionResult(zbus::zvariant::OwnedObjectPath, bool, Result<(), String>),
+    /// Automatic attempt after a failed connect
+    Reconnect(zbus::zvariant::OwnedObjectPath),
+    DismissConnectionError,
+    SetAutoReconnect(bool),
+    SelectGattCharacteristic(zbus::zvariant::OwnedObjectPath),
+    ReadGattCharacteristic(zbus::zvariant::OwnedObjectPath),
+    /// Writes the current input of the inspector
+    WriteGattCharacteristic(zbus::zvariant::OwnedObjectPath),
+    EditGattWrite(String),
+    SetGattValueFormat(ValueFormat),
+    ToggleGattNotify(zbus::zvariant::OwnedObjectPath),
+    ReceiveGattServices(
+        zbus::zvariant::OwnedObjectPath,
+        Result<Vec<GattService>, String>,
+    ),
+    /// Value that was read or written
+    ReceiveGattValue(zbus::zvariant::OwnedObjectPath, Vec<u8>),
+    GattNotifyChanged(zbus::zvariant::OwnedObjectPath, bool),
+    GattCallFailed(String),
+    GattValueChanged(zbus::zvariant::OwnedObjectPath, Vec<u8>),
+    ClearGattLog,
+    /// Adds or removes the device with this address from the favorites
+    ToggleFavorite(String),
+    /// Moves a favorite from one priority to another
+    MoveFavorite(usize, usize),
+    RemoveDevicePairing(zbus::zvariant::OwnedObjectPath),
+    SetBluetoothDeviceTrusted(zbus::zvariant::OwnedObjectPath, bool),
+    SetBluetoothDeviceBlocked(zbus::zvariant::OwnedObjectPath, bool),
+    SetBluetoothDeviceBattery(zbus::zvariant::OwnedObjectPath, u8),
+    DismissBatteryWarning,
+    SetDeviceFilter(DeviceFilter),
+    /// Index of the audio card and the profile to activate
+    SetDeviceAudioProfile(u32, String),
+    DeviceAudioProfileChanged(u32, String),
     AddBluetoothDevice(BluetoothDevice),
     RemoveBluetoothDevice(zbus::zvariant::OwnedObjectPath),
     SetPageId(BluetoothPageId),
+    ReceiveDeviceProfiles(zbus::zvariant::OwnedObjectPath, Vec<String>),
+    /// Audio card of a device, None without a connected audio profile
+    ReceiveDeviceCard(zbus::zvariant::OwnedObjectPath, Option<AudioCard>),
+    ReceiveAgentRequest(AgentRequest),
+    AgentPinChanged(String),
+    /// Accepts or rejects the current agent request
+    AgentReply(bool),
+    AgentTimeout(u32),
+    /// BlueZ canceled the request, e.g. because the device went away.
+    /// Only the request of the given device is closed, None closes any request
+    CancelAgentRequest(Option<zbus::zvariant::OwnedObjectPath>),
+    ChooseFileToSend(zbus::zvariant::OwnedObjectPath),
+    SendFile(zbus::zvariant::OwnedObjectPath, PathBuf),
+    TransferStarted(Transfer),
+    /// Transfer, new status and transferred bytes
+    TransferChanged(zbus::zvariant::OwnedObjectPath, TransferStatus, u64),
+    CancelTransfer,
+    DismissTransfer,
+    TransferRequested(IncomingTransfer),
+    /// Accepts or rejects the incoming transfer
+    AnswerTransfer(bool),
+    SetReceiveFiles(bool),
+    ReceiveFilesChanged(bool),
+    ChooseReceiveFolder,
+    SetReceiveFolder(PathBuf),
 }
 
 // This sucks
 pub async fn watch_bluetooth_dbus_signals(
     sender: &mut Sender<ReSetMessage>,
     conn: Arc<Connection>,
-    current_page_id: Arc<AtomicU8>,
 ) -> Result<(), zbus::Error> {
-    let proxy = BluetoothDbusProxy::new(&conn).await.expect("no proxy");
+    let proxy = BluetoothDbusProxy::new(&conn).await?;
     let mut signals = Proxy::receive_all_signals(&proxy.into_inner()).await?;
-    loop {
-        if current_page_id.load(std::sync::atomic::Ordering::SeqCst) != PageId::Bluetooth.into() {
-            sender.send(wrap(BluetoothMsg::GG)).await;
-            break;
-        } else {
-            sender.send(wrap(BluetoothMsg::GG)).await;
-        }
-        if let Some(msg) = signals.next().await {
-            match msg.header().member().unwrap().to_string().as_str() {
-                "BluetoothDeviceAdded" | "BluetoothDeviceChanged" => {
-                    let obj: BluetoothDevice = msg.body().deserialize()?;
-                    let _res = sender
-                        .send(wrap(BluetoothMsg::AddBluetoothDevice(obj)))
-                        .await;
-                }
-                "BluetoothDeviceRemoved" => {
-                    let obj: zbus::zvariant::OwnedObjectPath = msg.body().deserialize()?;
-                    let _res = sender
-                        .send(wrap(BluetoothMsg::RemoveBluetoothDevice(obj)))
-                        .await;
-                }
-                _ => (),
+    while let Some(msg) = signals.next().await {
+        match bluetooth_signal(&msg) {
+            Ok(Some(bluetooth_msg)) => {
+                let _res = sender.send(wrap(bluetooth_msg)).await;
             }
+            Ok(None) => (),
+            // a malformed signal does not end the live updates
+            Err(_) => LOG!("Skipped a Bluetooth signal that could not be read"),
         }
     }
     Ok(())
 }
 
+/// Reads a signal of the daemon, None for signals that are not handled
+fn bluetooth_signal(msg: &zbus::Message) -> Result<Option<BluetoothMsg>, zbus::Error> {
+    let bluetooth_msg = match msg.header().member().unwrap().to_string().as_str() {
+        "BluetoothDeviceAdded" | "BluetoothDeviceChanged" => {
+            let obj: BluetoothDevice = msg.body().deserialize()?;
+            Some(BluetoothMsg::AddBluetoothDevice(obj))
+        }
+        "BluetoothAdapterAdded" | "BluetoothAdapterChanged" => {
+            let adapter: BluetoothAdapter = msg.body().deserialize()?;
+            Some(BluetoothMsg::AddBluetoothAdapter(adapter))
+        }
+        "BluetoothAdapterRemoved" => {
+            let adapter: OwnedObjectPath = msg.body().deserialize()?;
+            Some(BluetoothMsg::RemoveBluetoothAdapter(adapter))
+        }
+        "BluetoothDeviceRemoved" => {
+            let obj: zbus::zvariant::OwnedObjectPath = msg.body().deserialize()?;
+            Some(BluetoothMsg::RemoveBluetoothDevice(obj))
+        }
+        "BluetoothDeviceBatteryChanged" => {
+            let (device, battery): (OwnedObjectPath, u8) = msg.body().deserialize()?;
+            Some(BluetoothMsg::SetBluetoothDeviceBattery(device, battery))
+        }
+        "RequestPinCode" => {
+            let device: OwnedObjectPath = msg.body().deserialize()?;
+            Some(BluetoothMsg::ReceiveAgentRequest(AgentRequest::PinCode(
+                device,
+            )))
+        }
+        "DisplayPasskey" => {
+            let (device, passkey, entered): (OwnedObjectPath, u32, u16) =
+                msg.body().deserialize()?;
+            Some(BluetoothMsg::ReceiveAgentRequest(
+                AgentRequest::DisplayPasskey(device, passkey, entered),
+            ))
+        }
+        "RequestConfirmation" => {
+            let (device, passkey): (OwnedObjectPath, u32) = msg.body().deserialize()?;
+            Some(BluetoothMsg::ReceiveAgentRequest(
+                AgentRequest::Confirmation(device, passkey),
+            ))
+        }
+        "AuthorizeService" => {
+            let (device, uuid): (OwnedObjectPath, String) = msg.body().deserialize()?;
+            Some(BluetoothMsg::ReceiveAgentRequest(
+                AgentRequest::AuthorizeService(device, uuid),
+            ))
+        }
+        "AgentRequestCanceled" => {
+            // older daemons send the signal without the device
+            let device: Option<OwnedObjectPath> = msg.body().deserialize().ok();
+            Some(BluetoothMsg::CancelAgentRequest(device))
+        }
+        "BluetoothGattValueChanged" => {
+            let (characteristic, value): (OwnedObjectPath, Vec<u8>) = msg.body().deserialize()?;
+            Some(BluetoothMsg::GattValueChanged(characteristic, value))
+        }
+        "BluetoothTransferChanged" => {
+            let (transfer, status, transferred): (OwnedObjectPath, String, u64) =
+                msg.body().deserialize()?;
+            Some(BluetoothMsg::TransferChanged(
+                transfer,
+                status.as_str().into(),
+                transferred,
+            ))
+        }
+        "BluetoothTransferRequested" => {
+            let (transfer, device, name, size): (OwnedObjectPath, OwnedObjectPath, String, u64) =
+                msg.body().deserialize()?;
+            Some(BluetoothMsg::TransferRequested(IncomingTransfer {
+                transfer,
+                device,
+                name,
+                size,
+            }))
+        }
+        _ => None,
+    };
+    Ok(bluetooth_msg)
+}
+
 fn wrap(msg: BluetoothMsg) -> ReSetMessage {
     ReSetMessage::SubMsgBluetooth(msg)
 }
 
+/// Available durations of a scan in seconds
+const SCAN_DURATIONS: [u32; 4] = [10, 30, 60, 120];
+/// Time between attempts to connect paired favorites that are not connected
+const AUTO_CONNECT_INTERVAL: Duration = Duration::from_secs(60);
+/// Periodic attempts per favorite before it is left alone until it reappears
+const MAX_AUTO_CONNECT_ATTEMPTS: u32 = 5;
+
+fn scan_tick(id: u32) -> Task<ReSetMessage> {
+    Task::perform(async_io::Timer::after(Duration::from_secs(1)), move |_| {
+        wrap(BluetoothMsg::ScanTick(id))
+    })
+}
+
+fn auto_connect_tick(id: u32) -> Task<ReSetMessage> {
+    Task::perform(async_io::Timer::after(AUTO_CONNECT_INTERVAL), move |_| {
+        wrap(BluetoothMsg::AutoConnectTick(id))
+    })
+}
+
+/// Runs a daemon call without blocking the update loop,
+/// `on_result` turns the result into the message that applies it to the model
+fn dbus_call<T>(
+    call: impl Future<Output = Result<T, zbus::Error>> + Send + 'static,
+    on_result: impl FnOnce(Result<T, String>) -> Option<BluetoothMsg> + Send + 'static,
+) -> Task<ReSetMessage>
+where
+    T: Send + 'static,
+{
+    Task::future(async move { on_result(call.await.map_err(daemon_error_message)).map(wrap) })
+        .and_then(Task::done)
+}
+
+/// The adapter or device is only changed locally once the daemon confirmed the change
+fn confirm_adapter<T>(
+    result: Result<T, String>,
+    changed: Option<BluetoothAdapter>,
+) -> Option<BluetoothMsg> {
+    result
+        .ok()
+        .and(changed)
+        .map(BluetoothMsg::AddBluetoothAdapter)
+}
+
+fn confirm_device<T>(
+    result: Result<T, String>,
+    changed: Option<BluetoothDevice>,
+) -> Option<BluetoothMsg> {
+    result
+        .ok()
+        .and(changed)
+        .map(BluetoothMsg::AddBluetoothDevice)
+}
+
+/// The daemon answers with an error when it has no adapter,
+/// only that error means None, every other one is passed on
+fn without_adapter<T>(result: Result<T, zbus::Error>) -> Result<Option<T>, zbus::Error> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(zbus::Error::MethodError(name, _, _)) if name.as_str() == NO_ADAPTER_ERROR => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
 fn to_map<T>(elements: Vec<T>) -> HashMap<OwnedObjectPath, T>
 where
     T: TPath,
@@ -116,117 +405,1270 @@ where
     map
 }
 
-impl<'a> BluetoothModel<'a> {
-    pub async fn new(conn: &zbus::Connection) -> Result<Self, zbus::Error> {
+impl BluetoothModel {
+    pub async fn new(
+        conn: &zbus::Connection,
+        config: BluetoothConfig,
+    ) -> Result<Self, zbus::Error> {
         let proxy = Arc::new(BluetoothDbusProxy::new(conn).await?);
-        let current_adapter = proxy.get_current_bluetooth_adapter().await?;
-        let adapters = to_map(proxy.get_bluetooth_adapters().await?);
-        let devices = to_map(proxy.get_bluetooth_devices().await?);
+        let audio_proxy = Arc::new(AudioDbusProxy::new(conn).await?);
+        // without an adapter there is nothing to list, adapters plugged in later are added
+        let current_adapter = without_adapter(proxy.get_current_bluetooth_adapter().await)?;
+        let adapters = without_adapter(proxy.get_bluetooth_adapters().await)?;
+        let devices = without_adapter(proxy.get_bluetooth_devices().await)?;
+        let adapters = to_map(adapters.unwrap_or_default());
+        let mut adapter_details = HashMap::new();
+        for path in adapters.keys() {
+            // older daemons do not know the details, the adapters work without them
+            if let Ok(details) = proxy.get_bluetooth_adapter_details(path.clone()).await {
+                adapter_details.insert(path.clone(), details);
+            }
+        }
+        let devices = to_map(devices.unwrap_or_default());
+        let rssi_history = devices
+            .values()
+            .map(|device| {
+                let mut history = RssiHistory::default();
+                history.push(device.rssi);
+                (device.path.clone(), history)
+            })
+            .collect();
         Ok(Self {
+            connection: conn.clone(),
             proxy,
+            audio_proxy,
             current_adapter,
             adapters,
+            adapter_details,
             devices,
             page_id: Default::default(),
+            alias_edit: None,
             is_scanning: false,
+            scan_remaining: 0,
+            scan_id: 0,
+            agent_request: None,
+            agent_request_count: 0,
+            device_profiles: Vec::new(),
+            device_card: None,
+            gatt: None,
+            config,
+            low_battery: HashSet::new(),
+            batteries: HashMap::new(),
+            battery_warning: None,
+            device_filter: Default::default(),
+            rssi_history,
+            connection_error: None,
+            reconnect_attempts: HashMap::new(),
+            auto_connect_queue: Vec::new(),
+            auto_connecting: None,
+            auto_connect_id: 0,
+            auto_connect_attempts: HashMap::new(),
+            manually_disconnected: HashSet::new(),
+            transfer: None,
+            incoming_transfer: None,
+            started: false,
         })
     }
 
-    pub async fn update(&mut self, msg: BluetoothMsg) -> Result<Task<ReSetMessage>, zbus::Error> {
-        let task = match msg {
-            BluetoothMsg::GG => {
-                println!("whakasdfksdajf");
-                Task::none()
+    /// Looks up the audio card of the device shown in the detail page,
+    /// the card only exists while an audio profile is connected
+    fn load_device_card(&self) -> Task<ReSetMessage> {
+        let (path, address) = match &self.page_id {
+            BluetoothPageId::Device(path) => match self.devices.get(path) {
+                Some(device) => (path.clone(), device.address.clone()),
+                None => return Task::none(),
+            },
+            _ => return Task::none(),
+        };
+        let proxy = self.audio_proxy.clone();
+        dbus_call(async move { proxy.list_cards().await }, move |result| {
+            let card = result
+                .ok()
+                .and_then(|cards| find_device_card(cards, &address));
+            Some(BluetoothMsg::ReceiveDeviceCard(path, card))
+        })
+    }
+
+    /// Stops all notifications the inspector subscribed to
+    fn close_gatt(&mut self) -> Task<ReSetMessage> {
+        let subscribed = match self.gatt.take() {
+            Some(gatt) => gatt.subscribed,
+            None => return Task::none(),
+        };
+        let proxy = self.proxy.clone();
+        dbus_call(
+            async move {
+                for characteristic in subscribed {
+                    ignore(proxy.stop_bluetooth_gatt_notify(characteristic).await);
+                }
+                Ok(())
+            },
+            |_| None,
+        )
+    }
+
+    fn adapter_state(&self) -> AdapterState {
+        let details = self
+            .current_adapter
+            .as_ref()
+            .and_then(|adapter| self.adapter_details.get(&adapter.path));
+        AdapterState::of(self.current_adapter.as_ref(), details)
+    }
+
+    /// Reloads the details, e.g. the rfkill state, after the adapter changed
+    fn load_adapter_details(&self, path: OwnedObjectPath) -> Task<ReSetMessage> {
+        let proxy = self.proxy.clone();
+        let target = path.clone();
+        dbus_call(
+            async move { proxy.get_bluetooth_adapter_details(target).await },
+            move |result| {
+                result
+                    .ok()
+                    .map(|details| BluetoothMsg::ReceiveAdapterDetails(path, details))
+            },
+        )
+    }
+
+    /// Connects the favorites and starts the listener once the adapter can be used
+    fn adapter_ready_changed(
+        &mut self,
+        was_ready: bool,
+    ) -> Result<Task<ReSetMessage>, zbus::Error> {
+        if !was_ready && self.adapter_state() == AdapterState::Ready {
+            self.manually_disconnected.clear();
+            self.auto_connect_attempts.clear();
+            Ok(Task::batch([
+                self.auto_connect_paired()?,
+                Task::done(wrap(BluetoothMsg::StartBluetoothListener)),
+            ]))
+        } else {
+            Ok(Task::none())
+        }
+    }
+
+    /// The adapter with a change applied, sent back once the daemon confirmed it
+    fn changed_adapter(
+        &self,
+        path: &OwnedObjectPath,
+        change: impl FnOnce(&mut BluetoothAdapter),
+    ) -> Option<BluetoothAdapter> {
+        let mut adapter = match self.adapters.get(path) {
+            Some(adapter) => adapter.clone(),
+            None => self
+                .current_adapter
+                .clone()
+                .filter(|adapter| adapter.path == *path)?,
+        };
+        change(&mut adapter);
+        Some(adapter)
+    }
+
+    /// Remembers when a device was connected or disconnected, kept across restarts
+    fn stamp_last_connected(&mut self, path: &OwnedObjectPath) {
+        let address = match self.devices.get(path) {
+            Some(device) => device.address.clone(),
+            None => return,
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        self.config.last_connected.insert(address, now);
+        self.save_config();
+    }
+
+    fn save_config(&self) {
+        let mut config = Config::load();
+        config.bluetooth = self.config.clone();
+        ignore(config.save());
+    }
+
+    /// Opens the file chooser without blocking the update loop
+    fn choose_file(
+        &self,
+        title: &'static str,
+        directory: bool,
+        on_choose: impl FnOnce(PathBuf) -> BluetoothMsg + Send + 'static,
+    ) -> Task<ReSetMessage> {
+        let connection = self.connection.clone();
+        Task::future(async move {
+            match choose_file(&connection, title, directory).await {
+                Ok(Some(path)) => Some(wrap(on_choose(path))),
+                _ => None,
             }
-            BluetoothMsg::GetBluetoothAdapters => {
-                let adapters = self.proxy.get_bluetooth_adapters().await?;
-                self.adapters = to_map(adapters);
-                Task::none()
+        })
+        .and_then(Task::done)
+    }
+
+    /// Connects or disconnects without blocking the update loop,
+    /// the daemon only answers once BlueZ gave up or succeeded
+    fn connection_task(
+        &mut self,
+        device: OwnedObjectPath,
+        connect: bool,
+    ) -> Result<Task<ReSetMessage>, zbus::Error> {
+        self.devices
+            .get_mut(&device)
+            .to_zbus_error()?
+            .conect_in_progress = true;
+        if self
+            .connection_error
+            .as_ref()
+            .is_some_and(|error| error.device == device)
+        {
+            self.connection_error = None;
+        }
+        let proxy = self.proxy.clone();
+        let target = device.clone();
+        Ok(Task::future(async move {
+            let call = async {
+                if connect {
+                    proxy.connect_to_bluetooth_device(target.clone()).await
+                } else {
+                    proxy.disconnect_from_bluetooth_device(target.clone()).await
+                }
+            };
+            let result = match select(
+                Box::pin(call),
+                Box::pin(async_io::Timer::after(CONNECT_TIMEOUT)),
+            )
+            .await
+            {
+                Either::Left((result, _)) => connection_result(result),
+                Either::Right(_) => {
+                    // BlueZ keeps connecting after the call is dropped,
+                    // it is cancelled so a retry does not run next to it
+                    if connect {
+                        ignore(proxy.disconnect_from_bluetooth_device(device.clone()).await);
+                    }
+                    Err(connection_error_reason("timeout"))
+                }
+            };
+            wrap(BluetoothMsg::ConnectionResult(device, connect, result))
+        }))
+    }
+
+    /// Position in the favorites, lower is connected first
+    fn favorite_priority(&self, path: &OwnedObjectPath) -> Option<usize> {
+        let device = self.devices.get(path)?;
+        self.config
+            .favorites
+            .iter()
+            .position(|address| address.eq_ignore_ascii_case(&device.address))
+    }
+
+    /// Queues the favorites among the devices, they are connected one after another
+    fn queue_auto_connect(
+        &mut self,
+        devices: Vec<OwnedObjectPath>,
+    ) -> Result<Task<ReSetMessage>, zbus::Error> {
+        for device in devices {
+            if self.favorite_priority(&device).is_some()
+                && !self.manually_disconnected.contains(&device)
+                && self.auto_connecting.as_ref() != Some(&device)
+                && !self.auto_connect_queue.contains(&device)
+            {
+                self.auto_connect_queue.push(device);
+            }
+        }
+        let mut queue = std::mem::take(&mut self.auto_connect_queue);
+        queue.sort_by_key(|device| self.favorite_priority(device));
+        self.auto_connect_queue = queue;
+        self.next_auto_connect()
+    }
+
+    fn next_auto_connect(&mut self) -> Result<Task<ReSetMessage>, zbus::Error> {
+        if self.auto_connecting.is_some() {
+            return Ok(Task::none());
+        }
+        while !self.auto_connect_queue.is_empty() {
+            let device = self.auto_connect_queue.remove(0);
+            if self.favorite_priority(&device).is_none() {
+                continue;
+            }
+            match self.devices.get(&device) {
+                Some(value) if !value.connected && !value.conect_in_progress => {
+                    self.auto_connecting = Some(device.clone());
+                    return self.connection_task(device, true);
+                }
+                _ => (),
+            }
+        }
+        Ok(Task::none())
+    }
+
+    /// Paired favorites are connected when the adapter is turned on
+    fn auto_connect_paired(&mut self) -> Result<Task<ReSetMessage>, zbus::Error> {
+        let devices = self
+            .devices
+            .values()
+            .filter(|device| device.paired)
+            .map(|device| device.path.clone())
+            .collect();
+        self.queue_auto_connect(devices)
+    }
+
+    fn start_scan_timer(&mut self) -> Task<ReSetMessage> {
+        self.is_scanning = true;
+        self.scan_remaining = self.config.scan_duration.max(1);
+        self.scan_id = self.scan_id.wrapping_add(1);
+        scan_tick(self.scan_id)
+    }
+
+    /// Warns once when a connected device drops below the configured threshold
+    fn check_battery(&mut self, path: &OwnedObjectPath) {
+        let device = match self.devices.get(path) {
+            Some(device) => device,
+            None => return,
+        };
+        match self.batteries.get(path).copied() {
+            Some(battery)
+                if device.connected && battery < self.config.battery_warning_threshold =>
+            {
+                if self.low_battery.insert(path.clone()) {
+                    self.battery_warning = Some(format!(
+                        "Battery of {} is low ({}%)",
+                        display_name(device),
+                        battery
+                    ));
+                }
             }
+            _ => {
+                self.low_battery.remove(path);
+            }
+        }
+    }
+
+    pub fn update(&mut self, msg: BluetoothMsg) -> Result<Task<ReSetMessage>, zbus::Error> {
+        let task = match msg {
             BluetoothMsg::StartBluetoothListener => {
-                self.is_scanning = true;
-                // is async in order to not block
-                self.proxy.start_bluetooth_listener().await?;
-                let func = async || -> ReSetMessage {
-                    thread::sleep(Duration::from_millis(10_000));
-                    wrap(BluetoothMsg::StopBluetoothScan)
+                if self.adapter_state() != AdapterState::Ready {
+                    return Ok(Task::none());
+                }
+                // the listener starts a discovery as well
+                let proxy = self.proxy.clone();
+                dbus_call(
+                    async move { proxy.start_bluetooth_listener().await },
+                    |result| result.ok().map(|_| BluetoothMsg::ScanStarted),
+                )
+            }
+            BluetoothMsg::EnterPage => {
+                // the listener keeps the devices up to date once it runs,
+                // later visits show them without a resync or a new scan
+                if self.started {
+                    return Ok(Task::none());
+                }
+                self.started = true;
+                let auto_connect = if self.adapter_state() == AdapterState::Ready {
+                    self.auto_connect_paired()?
+                } else {
+                    Task::none()
                 };
-                Task::future(func())
+                let receive_mode = if self.config.receive_files {
+                    let proxy = self.proxy.clone();
+                    let folder = self.config.receive_folder.clone();
+                    dbus_call(
+                        async move { proxy.set_bluetooth_receive_mode(true, folder).await },
+                        |_| None,
+                    )
+                } else {
+                    Task::none()
+                };
+                self.auto_connect_id = self.auto_connect_id.wrapping_add(1);
+                Task::batch([
+                    auto_connect,
+                    receive_mode,
+                    auto_connect_tick(self.auto_connect_id),
+                    Task::done(wrap(BluetoothMsg::StartBluetoothListener)),
+                ])
             }
-            BluetoothMsg::StopBluetoothListener => {
-                self.proxy.stop_bluetooth_listener().await?;
+            BluetoothMsg::LeavePage => {
+                // the listener keeps running for prompts, transfers and favorites
+                if !self.is_scanning {
+                    return Ok(self.close_gatt());
+                }
                 self.is_scanning = false;
-                Task::none()
+                self.scan_id = self.scan_id.wrapping_add(1);
+                let proxy = self.proxy.clone();
+                Task::batch([
+                    self.close_gatt(),
+                    dbus_call(async move { proxy.stop_bluetooth_scan().await }, |_| None),
+                ])
             }
             BluetoothMsg::SetBluetoothAdapter(adapter) => {
-                self.proxy.set_bluetooth_adapter(adapter).await?;
+                let selected = self.adapters.get(&adapter).cloned();
+                let proxy = self.proxy.clone();
+                dbus_call(
+                    async move { proxy.set_bluetooth_adapter(adapter).await },
+                    move |result| {
+                        result
+                            .ok()
+                            .and(selected)
+                            .map(BluetoothMsg::ReceiveCurrentAdapter)
+                    },
+                )
+            }
+            BluetoothMsg::ReceiveCurrentAdapter(adapter) => {
+                self.current_adapter = Some(adapter);
                 Task::none()
             }
+            BluetoothMsg::AddBluetoothAdapter(adapter) => {
+                let path = adapter.path.clone();
+                let was_ready = self.adapter_state() == AdapterState::Ready;
+                self.adapters.insert(path.clone(), adapter.clone());
+                match &self.current_adapter {
+                    Some(current_adapter) if current_adapter.path == path => {
+                        self.current_adapter = Some(adapter)
+                    }
+                    Some(_) => (),
+                    // the daemon picks a hot-plugged adapter as the default one
+                    None => self.current_adapter = Some(adapter),
+                }
+                Task::batch([
+                    self.adapter_ready_changed(was_ready)?,
+                    self.load_adapter_details(path),
+                ])
+            }
+            BluetoothMsg::ReceiveAdapterDetails(adapter, details) => {
+                let was_ready = self.adapter_state() == AdapterState::Ready;
+                self.adapter_details.insert(adapter, details);
+                self.adapter_ready_changed(was_ready)?
+            }
+            BluetoothMsg::RemoveBluetoothAdapter(adapter) => {
+                self.adapters.remove(&adapter);
+                self.adapter_details.remove(&adapter);
+                if self
+                    .current_adapter
+                    .as_ref()
+                    .is_some_and(|current_adapter| current_adapter.path == adapter)
+                {
+                    // shown until the daemon tells which adapter it picked instead
+                    self.current_adapter = self.adapters.values().next().cloned();
+                    self.is_scanning = false;
+                    self.devices.retain(|_, device| device.adapter != adapter);
+                    let proxy = self.proxy.clone();
+                    dbus_call(
+                        async move { proxy.get_current_bluetooth_adapter().await },
+                        move |result| match result {
+                            Ok(current_adapter) if current_adapter.path != adapter => {
+                                Some(BluetoothMsg::ReceiveCurrentAdapter(current_adapter))
+                            }
+                            _ => None,
+                        },
+                    )
+                } else {
+                    Task::none()
+                }
+            }
+            BluetoothMsg::UnblockBluetoothAdapter(adapter) => {
+                let proxy = self.proxy.clone();
+                let target = adapter.clone();
+                dbus_call(
+                    async move { proxy.unblock_bluetooth_adapter(target).await },
+                    move |result| {
+                        result
+                            .ok()
+                            .map(|_| BluetoothMsg::SetBluetoothAdapterEnabled(adapter, true))
+                    },
+                )
+            }
             BluetoothMsg::SetBluetoothAdapterEnabled(adapter, enabled) => {
-                self.proxy
-                    .set_bluetooth_adapter_enabled(adapter, enabled)
-                    .await?;
-                Task::none()
+                // turning it on connects the favorites once the adapter is ready
+                let changed = self.changed_adapter(&adapter, |value| value.powered = enabled);
+                let proxy = self.proxy.clone();
+                dbus_call(
+                    async move { proxy.set_bluetooth_adapter_enabled(adapter, enabled).await },
+                    move |result| confirm_adapter(result, changed),
+                )
             }
             BluetoothMsg::SetBluetoothAdapterDiscoverability(adapter, discoverability) => {
-                self.proxy
-                    .set_bluetooth_adapter_discoverability(adapter, discoverability)
-                    .await?;
-                Task::none()
+                let changed =
+                    self.changed_adapter(&adapter, |value| value.discoverable = discoverability);
+                let proxy = self.proxy.clone();
+                dbus_call(
+                    async move {
+                        proxy
+                            .set_bluetooth_adapter_discoverability(adapter, discoverability)
+                            .await
+                    },
+                    move |result| confirm_adapter(result, changed),
+                )
             }
             BluetoothMsg::SetBluetoothAdapterPairability(adapter, pairability) => {
-                self.proxy
-                    .set_bluetooth_adapter_pairability(adapter, pairability)
-                    .await?;
+                let changed = self.changed_adapter(&adapter, |value| value.pairable = pairability);
+                let proxy = self.proxy.clone();
+                dbus_call(
+                    async move {
+                        proxy
+                            .set_bluetooth_adapter_pairability(adapter, pairability)
+                            .await
+                    },
+                    move |result| confirm_adapter(result, changed),
+                )
+            }
+            BluetoothMsg::EditBluetoothAdapterAlias(adapter, alias) => {
+                self.alias_edit = Some((adapter, alias));
                 Task::none()
             }
+            BluetoothMsg::SetBluetoothAdapterAlias(adapter, alias) => {
+                self.alias_edit = None;
+                let changed =
+                    self.changed_adapter(&adapter, |value| value.alias.clone_from(&alias));
+                let proxy = self.proxy.clone();
+                dbus_call(
+                    async move { proxy.set_bluetooth_adapter_alias(adapter, alias).await },
+                    move |result| confirm_adapter(result, changed),
+                )
+            }
+            BluetoothMsg::SetBluetoothAdapterDiscoverableTimeout(adapter, timeout) => {
+                let changed = self
+                    .adapter_details
+                    .get(&adapter)
+                    .cloned()
+                    .map(|mut value| {
+                        value.discoverable_timeout = timeout;
+                        value
+                    });
+                let proxy = self.proxy.clone();
+                let target = adapter.clone();
+                dbus_call(
+                    async move {
+                        proxy
+                            .set_bluetooth_adapter_discoverable_timeout(target, timeout)
+                            .await
+                    },
+                    move |result| {
+                        result
+                            .ok()
+                            .and(changed)
+                            .map(|details| BluetoothMsg::ReceiveAdapterDetails(adapter, details))
+                    },
+                )
+            }
             BluetoothMsg::ConnectToBluetoothDevice(device) => {
-                self.devices
-                    .get_mut(&device)
-                    .to_zbus_error()?
-                    .conect_in_progress = true;
-                self.proxy.connect_to_bluetooth_device(device).await?;
-                Task::none()
+                self.reconnect_attempts.remove(&device);
+                self.manually_disconnected.remove(&device);
+                self.connection_task(device, true)?
             }
             BluetoothMsg::DisconnectFromBluetoothDevice(device) => {
-                self.devices
-                    .get_mut(&device)
-                    .to_zbus_error()?
-                    .conect_in_progress = true;
-                self.proxy.disconnect_from_bluetooth_device(device).await?;
+                self.reconnect_attempts.remove(&device);
+                self.manually_disconnected.insert(device.clone());
+                self.connection_task(device, false)?
+            }
+            BluetoothMsg::ConnectionResult(device, connect, result) => {
+                if let Some(value) = self.devices.get_mut(&device) {
+                    value.conect_in_progress = false;
+                }
+                if connect && result.is_ok() {
+                    self.auto_connect_attempts.remove(&device);
+                }
+                // failed automatic connects are expected when a favorite is not around
+                if self.auto_connecting.as_ref() == Some(&device) {
+                    self.auto_connecting = None;
+                    return self.next_auto_connect();
+                }
+                let reason = match result {
+                    Ok(()) => {
+                        self.reconnect_attempts.remove(&device);
+                        return Ok(Task::none());
+                    }
+                    Err(reason) => reason,
+                };
+                let mut message = format!(
+                    "Could not {} {}: {}",
+                    if connect {
+                        "connect to"
+                    } else {
+                        "disconnect from"
+                    },
+                    self.device_name(&device),
+                    reason
+                );
+                let mut task = Task::none();
+                if connect && self.config.auto_reconnect {
+                    let attempts = self.reconnect_attempts.entry(device.clone()).or_insert(0);
+                    if *attempts < self.config.reconnect_attempts {
+                        *attempts += 1;
+                        message.push_str(&format!(
+                            " (retry {} of {})",
+                            attempts, self.config.reconnect_attempts
+                        ));
+                        let target = device.clone();
+                        task = Task::perform(async_io::Timer::after(RECONNECT_DELAY), move |_| {
+                            wrap(BluetoothMsg::Reconnect(target.clone()))
+                        });
+                    }
+                }
+                self.connection_error = Some(ConnectionError {
+                    device,
+                    connect,
+                    message,
+                });
+                task
+            }
+            BluetoothMsg::Reconnect(device) => match self.devices.get(&device) {
+                Some(value) if !value.connected && !value.conect_in_progress => {
+                    self.connection_task(device, true)?
+                }
+                _ => {
+                    self.reconnect_attempts.remove(&device);
+                    Task::none()
+                }
+            },
+            BluetoothMsg::DismissConnectionError => {
+                self.connection_error = None;
                 Task::none()
             }
-            BluetoothMsg::RemoveDevicePairing(device) => {
-                self.proxy.remove_device_pairing(device).await?;
+            BluetoothMsg::SetAutoReconnect(enabled) => {
+                self.config.auto_reconnect = enabled;
+                self.save_config();
                 Task::none()
             }
+            BluetoothMsg::ToggleFavorite(address) => {
+                let favorites = &mut self.config.favorites;
+                match favorites
+                    .iter()
+                    .position(|favorite| favorite.eq_ignore_ascii_case(&address))
+                {
+                    Some(index) => {
+                        favorites.remove(index);
+                    }
+                    None => favorites.push(address),
+                }
+                self.save_config();
+                Task::none()
+            }
+            BluetoothMsg::MoveFavorite(from, to) => {
+                let favorites = &mut self.config.favorites;
+                if from < favorites.len() && to < favorites.len() {
+                    let favorite = favorites.remove(from);
+                    favorites.insert(to, favorite);
+                    self.save_config();
+                }
+                Task::none()
+            }
+            BluetoothMsg::RemoveDevicePairing(device) => {
+                let shown =
+                    matches!(&self.page_id, BluetoothPageId::Device(shown) if *shown == device);
+                let proxy = self.proxy.clone();
+                dbus_call(
+                    async move { proxy.remove_device_pairing(device).await },
+                    move |result| {
+                        result
+                            .ok()
+                            .filter(|_| shown)
+                            .map(|_| BluetoothMsg::SetPageId(BluetoothPageId::Devices))
+                    },
+                )
+            }
+            BluetoothMsg::SetBluetoothDeviceTrusted(device, trusted) => {
+                let changed = self.devices.get(&device).cloned().map(|mut value| {
+                    value.trusted = trusted;
+                    value
+                });
+                let proxy = self.proxy.clone();
+                dbus_call(
+                    async move { proxy.set_bluetooth_device_trusted(device, trusted).await },
+                    move |result| confirm_device(result, changed),
+                )
+            }
+            BluetoothMsg::SetBluetoothDeviceBlocked(device, blocked) => {
+                let changed = self.devices.get(&device).cloned().map(|mut value| {
+                    value.blocked = blocked;
+                    value
+                });
+                let proxy = self.proxy.clone();
+                dbus_call(
+                    async move { proxy.set_bluetooth_device_blocked(device, blocked).await },
+                    move |result| confirm_device(result, changed),
+                )
+            }
             BluetoothMsg::AddBluetoothDevice(bluetooth_device) => {
-                self.devices
-                    .insert(bluetooth_device.path(), bluetooth_device);
+                let path = bluetooth_device.path();
+                self.rssi_history
+                    .entry(path.clone())
+                    .or_default()
+                    .push(bluetooth_device.rssi);
+                let previous = self.devices.insert(path.clone(), bluetooth_device);
+                let connection_changed = previous
+                    .as_ref()
+                    .is_some_and(|previous| previous.connected != self.devices[&path].connected);
+                if connection_changed {
+                    self.stamp_last_connected(&path);
+                }
+                // RSSI is only reported during a discovery, so favorites
+                // are connected when they appear and by the periodic timer
+                let appeared = previous.is_none();
+                if appeared {
+                    self.manually_disconnected.remove(&path);
+                    self.auto_connect_attempts.remove(&path);
+                }
+                self.check_battery(&path);
+                let device_card = if connection_changed
+                    && matches!(&self.page_id, BluetoothPageId::Device(shown) if *shown == path)
+                {
+                    self.load_device_card()
+                } else {
+                    Task::none()
+                };
+                let auto_connect = if appeared {
+                    self.queue_auto_connect(vec![path])?
+                } else {
+                    Task::none()
+                };
+                Task::batch([device_card, auto_connect])
+            }
+            BluetoothMsg::SetBluetoothDeviceBattery(device, battery) => {
+                if battery <= 100 {
+                    self.batteries.insert(device.clone(), battery);
+                }
+                self.check_battery(&device);
+                Task::none()
+            }
+            BluetoothMsg::DismissBatteryWarning => {
+                self.battery_warning = None;
+                Task::none()
+            }
+            BluetoothMsg::SetDeviceFilter(device_filter) => {
+                self.device_filter = device_filter;
                 Task::none()
             }
             BluetoothMsg::RemoveBluetoothDevice(device_path) => {
                 self.devices.remove(&device_path);
+                self.rssi_history.remove(&device_path);
+                self.batteries.remove(&device_path);
                 Task::none()
             }
             BluetoothMsg::SetPageId(page_id) => {
+                let mut tasks = vec![self.close_gatt()];
+                match &page_id {
+                    BluetoothPageId::Device(device) => {
+                        // profiles are only informational, the page is shown without them
+                        self.device_profiles = Vec::new();
+                        let proxy = self.proxy.clone();
+                        let device = device.clone();
+                        let target = device.clone();
+                        tasks.push(dbus_call(
+                            async move { proxy.get_bluetooth_device_profiles(target).await },
+                            move |result| {
+                                result.ok().map(|profiles| {
+                                    BluetoothMsg::ReceiveDeviceProfiles(device, profiles)
+                                })
+                            },
+                        ));
+                    }
+                    BluetoothPageId::Gatt(device) => {
+                        self.gatt = Some(GattInspector::new(Vec::new()));
+                        let proxy = self.proxy.clone();
+                        let device = device.clone();
+                        let target = device.clone();
+                        tasks.push(dbus_call(
+                            async move { proxy.get_bluetooth_gatt_services(target).await },
+                            move |result| Some(BluetoothMsg::ReceiveGattServices(device, result)),
+                        ));
+                    }
+                    _ => (),
+                }
                 self.page_id = page_id;
+                tasks.push(self.load_device_card());
+                Task::batch(tasks)
+            }
+            BluetoothMsg::ReceiveDeviceProfiles(device, profiles) => {
+                if matches!(&self.page_id, BluetoothPageId::Device(shown) if *shown == device) {
+                    self.device_profiles = profiles;
+                }
                 Task::none()
             }
-            BluetoothMsg::StartBluetoothScan => {
-                self.proxy.start_bluetooth_scan().await?;
-                self.is_scanning = true;
+            BluetoothMsg::ReceiveDeviceCard(device, card) => {
+                if matches!(&self.page_id, BluetoothPageId::Device(shown) if *shown == device) {
+                    self.device_card = card;
+                }
+                Task::none()
+            }
+            BluetoothMsg::ReceiveGattServices(device, result) => {
+                if !matches!(&self.page_id, BluetoothPageId::Gatt(shown) if *shown == device) {
+                    return Ok(Task::none());
+                }
+                if let Some(gatt) = self.gatt.as_mut() {
+                    match result {
+                        Ok(services) => gatt.services = services,
+                        Err(error) => gatt.error = Some(error),
+                    }
+                }
+                Task::none()
+            }
+            BluetoothMsg::SelectGattCharacteristic(characteristic) => {
+                if let Some(gatt) = self.gatt.as_mut() {
+                    gatt.selected = Some(characteristic);
+                    gatt.write_input.clear();
+                    gatt.error = None;
+                }
                 Task::none()
             }
+            BluetoothMsg::ReadGattCharacteristic(characteristic) => {
+                let proxy = self.proxy.clone();
+                let target = characteristic.clone();
+                dbus_call(
+                    async move { proxy.read_bluetooth_gatt_characteristic(target).await },
+                    move |result| {
+                        Some(match result {
+                            Ok(value) => BluetoothMsg::ReceiveGattValue(characteristic, value),
+                            Err(error) => BluetoothMsg::GattCallFailed(error),
+                        })
+                    },
+                )
+            }
+            BluetoothMsg::WriteGattCharacteristic(characteristic) => {
+                let gatt = self.gatt.as_mut().to_zbus_error()?;
+                let width = gatt.values.get(&characteristic).map_or(0, Vec::len);
+                let value = match parse_value(&gatt.write_input, gatt.format, width) {
+                    Ok(value) => value,
+                    Err(error) => {
+                        gatt.error = Some(error);
+                        return Ok(Task::none());
+                    }
+                };
+                let proxy = self.proxy.clone();
+                let target = characteristic.clone();
+                let written = value.clone();
+                dbus_call(
+                    async move {
+                        proxy
+                            .write_bluetooth_gatt_characteristic(target, written)
+                            .await
+                    },
+                    move |result| {
+                        Some(match result {
+                            Ok(_) => BluetoothMsg::ReceiveGattValue(characteristic, value),
+                            Err(error) => BluetoothMsg::GattCallFailed(error),
+                        })
+                    },
+                )
+            }
+            BluetoothMsg::ReceiveGattValue(characteristic, value) => {
+                if let Some(gatt) = self.gatt.as_mut() {
+                    gatt.error = None;
+                    gatt.values.insert(characteristic, value);
+                }
+                Task::none()
+            }
+            BluetoothMsg::GattCallFailed(error) => {
+                if let Some(gatt) = self.gatt.as_mut() {
+                    gatt.error = Some(error);
+                }
+                Task::none()
+            }
+            BluetoothMsg::EditGattWrite(input) => {
+                if let Some(gatt) = self.gatt.as_mut() {
+                    gatt.write_input = input;
+                }
+                Task::none()
+            }
+            BluetoothMsg::SetGattValueFormat(format) => {
+                if let Some(gatt) = self.gatt.as_mut() {
+                    gatt.format = format;
+                    gatt.error = None;
+                }
+                Task::none()
+            }
+            BluetoothMsg::ToggleGattNotify(characteristic) => {
+                let subscribed = self
+                    .gatt
+                    .as_ref()
+                    .to_zbus_error()?
+                    .subscribed
+                    .contains(&characteristic);
+                let proxy = self.proxy.clone();
+                let target = characteristic.clone();
+                dbus_call(
+                    async move {
+                        if subscribed {
+                            proxy.stop_bluetooth_gatt_notify(target).await
+                        } else {
+                            proxy.start_bluetooth_gatt_notify(target).await
+                        }
+                    },
+                    move |result| {
+                        Some(match result {
+                            Ok(_) => BluetoothMsg::GattNotifyChanged(characteristic, !subscribed),
+                            Err(error) => BluetoothMsg::GattCallFailed(error),
+                        })
+                    },
+                )
+            }
+            BluetoothMsg::GattNotifyChanged(characteristic, subscribed) => {
+                if let Some(gatt) = self.gatt.as_mut() {
+                    if subscribed {
+                        gatt.subscribed.insert(characteristic);
+                    } else {
+                        gatt.subscribed.remove(&characteristic);
+                    }
+                }
+                Task::none()
+            }
+            BluetoothMsg::GattValueChanged(characteristic, value) => {
+                if let Some(gatt) = self.gatt.as_mut() {
+                    if gatt.subscribed.contains(&characteristic) {
+                        gatt.push_notification(characteristic, value);
+                    }
+                }
+                Task::none()
+            }
+            BluetoothMsg::ClearGattLog => {
+                if let Some(gatt) = self.gatt.as_mut() {
+                    gatt.log.clear();
+                }
+                Task::none()
+            }
+            BluetoothMsg::SetDeviceAudioProfile(card_index, profile) => {
+                let proxy = self.audio_proxy.clone();
+                let target = profile.clone();
+                dbus_call(
+                    async move { proxy.set_card_profile_of_device(card_index, target).await },
+                    move |result| {
+                        result
+                            .ok()
+                            .map(|_| BluetoothMsg::DeviceAudioProfileChanged(card_index, profile))
+                    },
+                )
+            }
+            BluetoothMsg::DeviceAudioProfileChanged(card_index, profile) => {
+                if let Some(card) = self.device_card.as_mut() {
+                    if card.index == card_index {
+                        card.active_profile = profile;
+                    }
+                }
+                Task::none()
+            }
+            BluetoothMsg::StartBluetoothScan => {
+                let proxy = self.proxy.clone();
+                dbus_call(
+                    async move { proxy.start_bluetooth_scan().await },
+                    |result| result.ok().map(|_| BluetoothMsg::ScanStarted),
+                )
+            }
+            BluetoothMsg::ScanStarted => self.start_scan_timer(),
             BluetoothMsg::StopBluetoothScan => {
-                self.proxy.stop_bluetooth_scan().await?;
+                if !self.is_scanning {
+                    return Ok(Task::none());
+                }
                 self.is_scanning = false;
+                self.scan_id = self.scan_id.wrapping_add(1);
+                let proxy = self.proxy.clone();
+                dbus_call(async move { proxy.stop_bluetooth_scan().await }, |_| None)
+            }
+            BluetoothMsg::ScanTick(id) => {
+                if !self.is_scanning || id != self.scan_id {
+                    return Ok(Task::none());
+                }
+                self.scan_remaining = self.scan_remaining.saturating_sub(1);
+                if self.scan_remaining == 0 {
+                    Task::done(wrap(BluetoothMsg::StopBluetoothScan))
+                } else {
+                    scan_tick(id)
+                }
+            }
+            BluetoothMsg::AutoConnectTick(id) => {
+                if id != self.auto_connect_id {
+                    return Ok(Task::none());
+                }
+                if self.adapter_state() != AdapterState::Ready {
+                    return Ok(auto_connect_tick(id));
+                }
+                // absent favorites are not paged forever
+                let mut devices = Vec::new();
+                for device in self.devices.values() {
+                    if !device.paired
+                        || device.connected
+                        || self.manually_disconnected.contains(&device.path)
+                        || self.favorite_priority(&device.path).is_none()
+                    {
+                        continue;
+                    }
+                    let attempts = self
+                        .auto_connect_attempts
+                        .entry(device.path.clone())
+                        .or_insert(0);
+                    if *attempts < MAX_AUTO_CONNECT_ATTEMPTS {
+                        *attempts += 1;
+                        devices.push(device.path.clone());
+                    }
+                }
+                Task::batch([self.queue_auto_connect(devices)?, auto_connect_tick(id)])
+            }
+            BluetoothMsg::SetScanDuration(duration) => {
+                self.config.scan_duration = duration;
+                self.save_config();
+                Task::none()
+            }
+            BluetoothMsg::ReceiveAgentRequest(request) => {
+                // DisplayPasskey is sent again for every digit typed on the device
+                if let Some(pending) = self.agent_request.as_mut() {
+                    if matches!(pending.request, AgentRequest::DisplayPasskey(..))
+                        && pending.request.device() == request.device()
+                    {
+                        pending.request = request;
+                        return Ok(Task::none());
+                    }
+                }
+                self.agent_request_count = self.agent_request_count.wrapping_add(1);
+                let id = self.agent_request_count;
+                let replaced = self.agent_request.replace(PendingAgentRequest {
+                    id,
+                    request,
+                    pin: String::new(),
+                });
+                let timeout = Task::perform(async_io::Timer::after(AGENT_TIMEOUT), move |_| {
+                    wrap(BluetoothMsg::AgentTimeout(id))
+                });
+                // BlueZ waits for an answer to every request except DisplayPasskey
+                match replaced {
+                    Some(replaced)
+                        if !matches!(replaced.request, AgentRequest::DisplayPasskey(..)) =>
+                    {
+                        let device = replaced.request.device().clone();
+                        let proxy = self.proxy.clone();
+                        Task::batch([
+                            timeout,
+                            dbus_call(
+                                async move { proxy.cancel_bluetooth_request(device).await },
+                                |_| None,
+                            ),
+                        ])
+                    }
+                    _ => timeout,
+                }
+            }
+            BluetoothMsg::AgentPinChanged(pin) => {
+                if let Some(pending) = self.agent_request.as_mut() {
+                    pending.pin = pin;
+                }
+                Task::none()
+            }
+            BluetoothMsg::AgentReply(accept) => match self.agent_request.take() {
+                Some(pending) => {
+                    let device = pending.request.device().clone();
+                    let proxy = self.proxy.clone();
+                    dbus_call(
+                        async move {
+                            match pending.request {
+                                AgentRequest::PinCode(_) if accept && pending.valid_pin() => {
+                                    proxy.provide_bluetooth_pin_code(device, pending.pin).await
+                                }
+                                AgentRequest::Confirmation(..)
+                                | AgentRequest::AuthorizeService(..) => {
+                                    proxy.confirm_bluetooth_request(device, accept).await
+                                }
+                                _ => proxy.cancel_bluetooth_request(device).await,
+                            }
+                        },
+                        |_| None,
+                    )
+                }
+                None => Task::none(),
+            },
+            BluetoothMsg::AgentTimeout(id) => match self.agent_request.take() {
+                Some(pending) if pending.id == id => {
+                    let device = pending.request.device().clone();
+                    let proxy = self.proxy.clone();
+                    dbus_call(
+                        async move { proxy.cancel_bluetooth_request(device).await },
+                        |_| None,
+                    )
+                }
+                pending => {
+                    self.agent_request = pending;
+                    Task::none()
+                }
+            },
+            BluetoothMsg::CancelAgentRequest(device) => {
+                if self.agent_request.as_ref().is_some_and(|pending| {
+                    device
+                        .as_ref()
+                        .is_none_or(|device| pending.request.device() == device)
+                }) {
+                    self.agent_request = None;
+                }
+                Task::none()
+            }
+            BluetoothMsg::ChooseFileToSend(device) => {
+                self.choose_file("Send file", false, move |file| {
+                    BluetoothMsg::SendFile(device, file)
+                })
+            }
+            BluetoothMsg::SendFile(device, file) => {
+                let proxy = self.proxy.clone();
+                let target = device.clone();
+                let source = file.to_string_lossy().to_string();
+                let size_of = file.clone();
+                dbus_call(
+                    async move {
+                        let path = proxy.send_file_to_bluetooth_device(target, source).await?;
+                        let size = std::fs::metadata(size_of)
+                            .map(|metadata| metadata.len())
+                            .unwrap_or(0);
+                        Ok((path, size))
+                    },
+                    move |result| {
+                        let (path, size) = result.ok()?;
+                        Some(BluetoothMsg::TransferStarted(Transfer {
+                            path,
+                            device,
+                            name: file
+                                .file_name()
+                                .map(|name| name.to_string_lossy().to_string())
+                                .unwrap_or_default(),
+                            size,
+                            transferred: 0,
+                            status: TransferStatus::Queued,
+                            incoming: false,
+                        }))
+                    },
+                )
+            }
+            BluetoothMsg::TransferStarted(transfer) => {
+                self.transfer = Some(transfer);
+                Task::none()
+            }
+            BluetoothMsg::TransferChanged(path, status, transferred) => {
+                if let Some(transfer) = self.transfer.as_mut() {
+                    if transfer.path == path {
+                        transfer.status = status;
+                        transfer.transferred = transferred;
+                    }
+                }
                 Task::none()
             }
+            BluetoothMsg::CancelTransfer => match self.transfer.take() {
+                Some(transfer) if !transfer.status.is_finished() => {
+                    let proxy = self.proxy.clone();
+                    dbus_call(
+                        async move { proxy.cancel_bluetooth_transfer(transfer.path).await },
+                        |_| None,
+                    )
+                }
+                _ => Task::none(),
+            },
+            BluetoothMsg::DismissTransfer => {
+                self.transfer = None;
+                Task::none()
+            }
+            BluetoothMsg::TransferRequested(incoming) => {
+                // only one transfer is shown at a time
+                if self
+                    .transfer
+                    .as_ref()
+                    .is_some_and(|transfer| !transfer.status.is_finished())
+                    || self.incoming_transfer.is_some()
+                {
+                    let proxy = self.proxy.clone();
+                    dbus_call(
+                        async move {
+                            proxy
+                                .answer_bluetooth_transfer(incoming.transfer, false)
+                                .await
+                        },
+                        |_| None,
+                    )
+                } else {
+                    self.incoming_transfer = Some(incoming);
+                    Task::none()
+                }
+            }
+            BluetoothMsg::AnswerTransfer(accept) => match self.incoming_transfer.take() {
+                Some(incoming) => {
+                    let proxy = self.proxy.clone();
+                    let transfer = incoming.transfer.clone();
+                    dbus_call(
+                        async move { proxy.answer_bluetooth_transfer(transfer, accept).await },
+                        move |result| {
+                            result
+                                .ok()
+                                .filter(|_| accept)
+                                .map(|_| BluetoothMsg::TransferStarted(incoming.into()))
+                        },
+                    )
+                }
+                None => Task::none(),
+            },
+            BluetoothMsg::SetReceiveFiles(enabled) => {
+                let proxy = self.proxy.clone();
+                let folder = self.config.receive_folder.clone();
+                dbus_call(
+                    async move { proxy.set_bluetooth_receive_mode(enabled, folder).await },
+                    move |result| {
+                        result
+                            .ok()
+                            .map(|_| BluetoothMsg::ReceiveFilesChanged(enabled))
+                    },
+                )
+            }
+            BluetoothMsg::ReceiveFilesChanged(enabled) => {
+                self.config.receive_files = enabled;
+                self.save_config();
+                Task::none()
+            }
+            BluetoothMsg::ChooseReceiveFolder => {
+                self.choose_file("Receive files into", true, BluetoothMsg::SetReceiveFolder)
+            }
+            BluetoothMsg::SetReceiveFolder(folder) => {
+                self.config.receive_folder = folder.to_string_lossy().to_string();
+                self.save_config();
+                if self.config.receive_files {
+                    let proxy = self.proxy.clone();
+                    let folder = self.config.receive_folder.clone();
+                    dbus_call(
+                        async move { proxy.set_bluetooth_receive_mode(true, folder).await },
+                        |_| None,
+                    )
+                } else {
+                    Task::none()
+                }
+            }
         };
         Ok(task)
     }
 
+    /// Battery levels of the connected devices, one device per line
+    pub fn battery_summary(&self) -> Option<String> {
+        let lines: Vec<String> = self
+            .devices
+            .values()
+            .filter(|device| device.connected)
+            .filter_map(|device| {
+                self.batteries
+                    .get(&device.path)
+                    .map(|battery| format!("{}: {}%", display_name(device), battery))
+            })
+            .collect();
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+
+    fn device_name(&self, path: &OwnedObjectPath) -> String {
+        match self.devices.get(path) {
+            Some(device) => display_name(device).to_string(),
+            None => path.to_string(),
+        }
+    }
+
+    /// Dialog for a running pairing request or an incoming file,
+    /// shown on top of the whole window
+    pub fn dialog(&self) -> Option<Element<'_, ReSetMessage>> {
+        if let Some(pending) = self.agent_request.as_ref() {
+            let device_name = self.device_name(pending.request.device());
+            return Some(agent_dialog_view(pending, device_name));
+        }
+        let incoming = self.incoming_transfer.as_ref()?;
+        Some(transfer_dialog_view(
+            incoming,
+            self.device_name(&incoming.device),
+        ))
+    }
+
     pub fn view(&self) -> Element<ReSetMessage> {
         let devices = column!(
             oxiced::widgets::oxi_button::button(
@@ -242,20 +1684,30 @@ impl<'a> BluetoothModel<'a> {
             oxiced::widgets::oxi_button::button(
                 row!(
                     text(if self.is_scanning {
-                        "Scanning"
+                        format!("Scanning ({}s)", self.scan_remaining)
                     } else {
-                        "Start scan"
+                        "Start scan".to_string()
                     })
                     .width(Length::Fill)
                     .size(20),
                     if self.is_scanning {
-                        row!(Circular::new()
-                            .easing(&STANDARD)
-                            .cycle_duration(Duration::from_millis(3000)))
+                        row!(
+                            Circular::new()
+                                .easing(&STANDARD)
+                                .cycle_duration(Duration::from_millis(3000)),
+                            oxiced::widgets::oxi_button::button(
+                                text("Stop"),
+                                ButtonVariant::Primary
+                            )
+                            .on_press(wrap(BluetoothMsg::StopBluetoothScan))
+                        )
+                        .spacing(10)
+                        .align_y(Vertical::Center)
                     } else {
                         row!(icon_widget(Icon::Refresh).width(Length::Shrink))
                     }
                 )
+                .align_y(Vertical::Center)
                 .width(Length::Fill),
                 ButtonVariant::RowEntry
             )
@@ -265,20 +1717,49 @@ impl<'a> BluetoothModel<'a> {
                 Some(wrap(BluetoothMsg::StartBluetoothScan))
             })
             .width(Length::Fill),
+            CustomPickList::new(
+                PickerVariant::ComboPicker(ComboPickerTitle::new(
+                    "Scan duration",
+                    Some("Seconds until the scan stops")
+                )),
+                SCAN_DURATIONS.to_vec(),
+                Some(self.config.scan_duration),
+                |duration| wrap(BluetoothMsg::SetScanDuration(duration)),
+            ),
+            row!(
+                text("Reconnect automatically").width(Length::Fill),
+                oxiced::widgets::oxi_toggler::toggler(self.config.auto_reconnect)
+                    .on_toggle(|value| wrap(BluetoothMsg::SetAutoReconnect(value)))
+            ),
+            favorites_view(
+                self.config
+                    .favorites
+                    .iter()
+                    .map(|address| {
+                        let name = self
+                            .devices
+                            .values()
+                            .find(|device| device.address.eq_ignore_ascii_case(address))
+                            .map(|device| display_name(device).to_string());
+                        (address.clone(), name)
+                    })
+                    .collect()
+            ),
+            device_filter_view(&self.device_filter),
             bluetooth_device_buttons(
-                &self
-                    .devices
-                    .values()
-                    .filter(|value| !value.connected)
-                    .collect(),
+                &self.device_filter.apply(
+                    self.devices.values().filter(|value| !value.connected),
+                    &self.config.last_connected
+                ),
+                &self.batteries,
                 BluetoothButtonVariant::Connect
             ),
             bluetooth_device_buttons(
-                &self
-                    .devices
-                    .values()
-                    .filter(|value| value.connected)
-                    .collect(),
+                &self.device_filter.apply(
+                    self.devices.values().filter(|value| value.connected),
+                    &self.config.last_connected
+                ),
+                &self.batteries,
                 BluetoothButtonVariant::Disconnect
             ),
         )
@@ -295,13 +1776,107 @@ impl<'a> BluetoothModel<'a> {
             )
             .on_press(wrap(BluetoothMsg::SetPageId(BluetoothPageId::Devices)))
             .width(Length::Fill),
-            bluetooth_adapter_view(&self.current_adapter, &self.adapters.values().collect())
+            bluetooth_adapter_view(
+                self.current_adapter.as_ref(),
+                &self.adapters.values().collect(),
+                &self.adapter_details,
+                &self.alias_edit
+            ),
+            receive_files_view(self.config.receive_files, &self.config.receive_folder)
         )
         .padding(20)
         .spacing(30);
-        match self.page_id {
-            BluetoothPageId::Devices => devices.into(),
-            BluetoothPageId::Adapter => adapter.into(),
-        }
+        let warning = self.battery_warning.as_ref().map(|message| {
+            container(error_banner(
+                message,
+                wrap(BluetoothMsg::DismissBatteryWarning),
+            ))
+            .padding(Padding::new(20.0).bottom(0))
+        });
+        let connection_error = self.connection_error.as_ref().map(|error| {
+            let retry = if error.connect {
+                BluetoothMsg::ConnectToBluetoothDevice(error.device.clone())
+            } else {
+                BluetoothMsg::DisconnectFromBluetoothDevice(error.device.clone())
+            };
+            container(error_banner_with_action(
+                &error.message,
+                Some(("Retry", wrap(retry))),
+                wrap(BluetoothMsg::DismissConnectionError),
+            ))
+            .padding(Padding::new(20.0).bottom(0))
+        });
+        let transfer = self.transfer.as_ref().map(|transfer| {
+            container(transfer_view(transfer, self.device_name(&transfer.device)))
+                .padding(Padding::new(20.0).bottom(0))
+        });
+        let state = self.adapter_state();
+        // the adapter settings stay reachable to turn it on
+        let usable = match &self.page_id {
+            BluetoothPageId::Adapter => state != AdapterState::NoAdapter,
+            _ => state == AdapterState::Ready,
+        };
+        let page: Element<ReSetMessage> = if !usable {
+            adapter_state_view(state, self.current_adapter.as_ref())
+        } else {
+            match &self.page_id {
+                BluetoothPageId::Devices => devices.into(),
+                BluetoothPageId::Adapter => adapter.into(),
+                BluetoothPageId::Device(path) => {
+                    let back = oxiced::widgets::oxi_button::button(
+                        row!(
+                            text("Devices").width(Length::Fill).size(20),
+                            icon_widget(Icon::ChevronLeft).width(Length::Shrink)
+                        )
+                        .width(Length::Fill),
+                        ButtonVariant::RowEntry,
+                    )
+                    .on_press(wrap(BluetoothMsg::SetPageId(BluetoothPageId::Devices)))
+                    .width(Length::Fill);
+                    let transfer_running = self
+                        .transfer
+                        .as_ref()
+                        .is_some_and(|transfer| !transfer.status.is_finished());
+                    let detail = match self.devices.get(path) {
+                        Some(device) => device_detail_view(
+                            device,
+                            self.batteries.get(path).copied(),
+                            &self.device_profiles,
+                            self.device_card.as_ref(),
+                            transfer_running,
+                            self.favorite_priority(path).is_some(),
+                            self.rssi_history.get(path),
+                        ),
+                        None => text("This device is no longer available").into(),
+                    };
+                    column!(back, detail).padding(20).spacing(30).into()
+                }
+                BluetoothPageId::Gatt(path) => {
+                    let back = oxiced::widgets::oxi_button::button(
+                        row!(
+                            text("Device").width(Length::Fill).size(20),
+                            icon_widget(Icon::ChevronLeft).width(Length::Shrink)
+                        )
+                        .width(Length::Fill),
+                        ButtonVariant::RowEntry,
+                    )
+                    .on_press(wrap(BluetoothMsg::SetPageId(BluetoothPageId::Device(
+                        path.clone(),
+                    ))))
+                    .width(Length::Fill);
+                    let inspector = match &self.gatt {
+                        Some(gatt) => gatt_view(gatt, self.device_name(path)),
+                        None => text("No GATT services loaded").into(),
+                    };
+                    column!(back, inspector).padding(20).spacing(30).into()
+                }
+            }
+        };
+        column![]
+            .push_maybe(warning)
+            .push_maybe(connection_error)
+            .push_maybe(transfer)
+            .push(page)
+            .into()
     }
 }
@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use crate::utils::daemon_error_message;
+
+/// Connection attempts that take longer are given up
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+/// Wait between automatic reconnect attempts
+pub const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// A failed connect or disconnect, shown until dismissed or retried
+#[derive(Debug, Clone)]
+pub struct ConnectionError {
+    pub device: zbus::zvariant::OwnedObjectPath,
+    /// Whether a connect or a disconnect failed
+    pub connect: bool,
+    pub message: String,
+}
+
+/// Turns the error codes of BlueZ into a readable reason,
+/// unknown errors are shown as they are
+pub fn connection_error_reason(error: &str) -> String {
+    let code = error
+        .strip_prefix("br-connection-")
+        .or_else(|| error.strip_prefix("le-connection-"))
+        .unwrap_or(error);
+    match code {
+        "page-timeout" => "Page Timeout, the device did not answer",
+        "timeout" => "The connection timed out",
+        "profile-unavailable" | "not-supported" => "The device offers no supported profile",
+        "adapter-not-powered" => "The adapter is turned off",
+        "already-connected" => "The device is already connected",
+        "busy" => "The adapter is busy",
+        "concurrent-connection-limit" => "Too many devices are connected",
+        "refused" => "The device refused the connection",
+        "aborted-by-remote" => "The device aborted the connection",
+        "aborted-by-local" | "canceled" => "The connection was canceled",
+        "key-missing" => "The pairing key is missing, pair the device again",
+        "sdp-search" => "The services of the device could not be read",
+        "lmp-protocol-error" => "Protocol error",
+        "unknown" => "The connection failed for an unknown reason",
+        // the message of the kernel is passed on with more text after it
+        _ if error.contains("Host is down") => {
+            "Host is down, the device is out of range or turned off"
+        }
+        _ => return error.to_string(),
+    }
+    .to_string()
+}
+
+/// Result of a connect or disconnect call, failures carry the readable reason.
+/// The daemon answers with a D-Bus error carrying the BlueZ error,
+/// older daemons only return false without a reason
+pub fn connection_result(result: zbus::Result<bool>) -> Result<(), String> {
+    match result {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(connection_error_reason("unknown")),
+        Err(error) => Err(connection_error_reason(&daemon_error_message(error))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_bluez_codes() {
+        assert_eq!(
+            connection_error_reason("br-connection-page-timeout"),
+            "Page Timeout, the device did not answer"
+        );
+        assert_eq!(
+            connection_error_reason("le-connection-refused"),
+            "The device refused the connection"
+        );
+        assert_eq!(
+            connection_error_reason("timeout"),
+            "The connection timed out"
+        );
+        assert_eq!(
+            connection_error_reason("br-connection-not-supported"),
+            "The device offers no supported profile"
+        );
+    }
+
+    #[test]
+    fn maps_host_is_down() {
+        let reason = "Host is down, the device is out of range or turned off";
+        assert_eq!(connection_error_reason("Host is down"), reason);
+        assert_eq!(connection_error_reason("Host is down (112)"), reason);
+    }
+
+    #[test]
+    fn keeps_unknown_errors() {
+        assert_eq!(
+            connection_error_reason("Software caused connection abort"),
+            "Software caused connection abort"
+        );
+        assert_eq!(connection_error_reason(""), "");
+    }
+
+    #[test]
+    fn reads_call_results() {
+        assert_eq!(connection_result(Ok(true)), Ok(()));
+        assert_eq!(
+            connection_result(Ok(false)),
+            Err("The connection failed for an unknown reason".to_string())
+        );
+        assert_eq!(
+            connection_result(Err(zbus::Error::Failure(
+                "br-connection-page-timeout".to_string()
+            ))),
+            Err("Page Timeout, the device did not answer".to_string())
+        );
+    }
+}
@@ -51,6 +51,39 @@ impl TPath for BluetoothAdapter {
     }
 }
 
+/// Properties of an adapter that are not part of [`BluetoothAdapter`],
+/// fetched separately so the adapter keeps its wire format
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Type)]
+pub struct BluetoothAdapterDetails {
+    pub address: String,
+    pub manufacturer: String,
+    /// Seconds until discoverable is turned off again, 0 keeps it on
+    pub discoverable_timeout: u32,
+    /// Supported roles like central and peripheral
+    pub roles: Vec<String>,
+    /// PowerState of BlueZ, off-blocked if rfkill blocks the adapter
+    pub power_state: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Type)]
+pub struct GattCharacteristic {
+    pub path: OwnedObjectPath,
+    pub uuid: String,
+    /// Supported operations like read, write and notify
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Type)]
+pub struct GattService {
+    pub path: OwnedObjectPath,
+    pub uuid: String,
+    pub primary: bool,
+    pub characteristics: Vec<GattCharacteristic>,
+}
+
+/// Name of the error the daemon answers with while there is no adapter
+pub const NO_ADAPTER_ERROR: &str = "org.Xetibo.ReSet.Bluetooth.NoAdapter";
+
 // TODO beforepr finish and put in lib
 #[proxy(
     default_service = "org.Xetibo.ReSet.Daemon",
@@ -80,9 +113,62 @@ pub trait BluetoothDbus {
         obj: OwnedObjectPath,
         pariable: bool,
     ) -> zbus::Result<bool>;
+    fn unblock_bluetooth_adapter(&self, obj: OwnedObjectPath) -> zbus::Result<bool>;
+    fn set_bluetooth_adapter_alias(
+        &self,
+        obj: OwnedObjectPath,
+        alias: String,
+    ) -> zbus::Result<bool>;
+    fn get_bluetooth_adapter_details(
+        &self,
+        obj: OwnedObjectPath,
+    ) -> zbus::Result<BluetoothAdapterDetails>;
+    fn set_bluetooth_adapter_discoverable_timeout(
+        &self,
+        obj: OwnedObjectPath,
+        timeout: u32,
+    ) -> zbus::Result<bool>;
     fn get_bluetooth_devices(&self) -> zbus::Result<Vec<BluetoothDevice>>;
     fn connect_to_bluetooth_device(&self, obj: OwnedObjectPath) -> zbus::Result<bool>;
     fn disconnect_from_bluetooth_device(&self, obj: OwnedObjectPath) -> zbus::Result<bool>;
     fn remove_device_pairing(&self, obj: OwnedObjectPath) -> zbus::Result<bool>;
     fn get_connected_bluetooth_devices(&self) -> zbus::Result<Vec<BluetoothDevice>>;
+    fn get_bluetooth_device_profiles(&self, obj: OwnedObjectPath) -> zbus::Result<Vec<String>>;
+    fn set_bluetooth_device_trusted(
+        &self,
+        obj: OwnedObjectPath,
+        trusted: bool,
+    ) -> zbus::Result<bool>;
+    fn set_bluetooth_device_blocked(
+        &self,
+        obj: OwnedObjectPath,
+        blocked: bool,
+    ) -> zbus::Result<bool>;
+    // answers to the pairing agent requests
+    fn provide_bluetooth_pin_code(&self, obj: OwnedObjectPath, pin: String) -> zbus::Result<bool>;
+    fn confirm_bluetooth_request(&self, obj: OwnedObjectPath, accept: bool) -> zbus::Result<bool>;
+    fn cancel_bluetooth_request(&self, obj: OwnedObjectPath) -> zbus::Result<bool>;
+    // GATT of Bluetooth LE devices
+    fn get_bluetooth_gatt_services(&self, obj: OwnedObjectPath) -> zbus::Result<Vec<GattService>>;
+    fn read_bluetooth_gatt_characteristic(&self, obj: OwnedObjectPath) -> zbus::Result<Vec<u8>>;
+    fn write_bluetooth_gatt_characteristic(
+        &self,
+        obj: OwnedObjectPath,
+        value: Vec<u8>,
+    ) -> zbus::Result<bool>;
+    fn start_bluetooth_gatt_notify(&self, obj: OwnedObjectPath) -> zbus::Result<bool>;
+    fn stop_bluetooth_gatt_notify(&self, obj: OwnedObjectPath) -> zbus::Result<bool>;
+    // OBEX Object Push
+    fn send_file_to_bluetooth_device(
+        &self,
+        obj: OwnedObjectPath,
+        file: String,
+    ) -> zbus::Result<OwnedObjectPath>;
+    fn cancel_bluetooth_transfer(&self, transfer: OwnedObjectPath) -> zbus::Result<bool>;
+    fn set_bluetooth_receive_mode(&self, enabled: bool, folder: String) -> zbus::Result<bool>;
+    fn answer_bluetooth_transfer(
+        &self,
+        transfer: OwnedObjectPath,
+        accept: bool,
+    ) -> zbus::Result<bool>;
 }
@@ -0,0 +1,267 @@
+use std::{collections::HashMap, fmt::Display};
+
+use super::{dbus_interface::BluetoothDevice, device_kind::DeviceKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeviceSort {
+    #[default]
+    Name,
+    Signal,
+    LastConnected,
+}
+
+impl DeviceSort {
+    pub const ALL: [DeviceSort; 3] = [Self::Name, Self::Signal, Self::LastConnected];
+}
+
+impl Display for DeviceSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Name => "Name",
+            Self::Signal => "Signal strength",
+            Self::LastConnected => "Last connected",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KindFilter {
+    #[default]
+    All,
+    Kind(DeviceKind),
+}
+
+impl KindFilter {
+    pub fn options() -> Vec<KindFilter> {
+        std::iter::once(Self::All)
+            .chain(DeviceKind::ALL.into_iter().map(Self::Kind))
+            .collect()
+    }
+}
+
+impl Display for KindFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::All => f.write_str("All types"),
+            Self::Kind(kind) => kind.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignalFilter {
+    #[default]
+    Any,
+    /// Minimum RSSI in dBm
+    Minimum(i16),
+}
+
+impl SignalFilter {
+    pub const ALL: [SignalFilter; 5] = [
+        Self::Any,
+        Self::Minimum(-90),
+        Self::Minimum(-80),
+        Self::Minimum(-70),
+        Self::Minimum(-60),
+    ];
+}
+
+impl Display for SignalFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => f.write_str("Any signal"),
+            Self::Minimum(rssi) => write!(f, "{} dBm", rssi),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeviceFilter {
+    pub search: String,
+    pub hide_unnamed: bool,
+    pub only_paired: bool,
+    pub kind: KindFilter,
+    pub signal: SignalFilter,
+    pub sort: DeviceSort,
+}
+
+impl Default for DeviceFilter {
+    fn default() -> Self {
+        Self {
+            search: String::new(),
+            // anonymous beacons flood the list during a scan
+            hide_unnamed: true,
+            only_paired: false,
+            kind: KindFilter::All,
+            signal: SignalFilter::Any,
+            sort: DeviceSort::Name,
+        }
+    }
+}
+
+/// Alias of the device, or the address for devices without a name
+pub fn display_name(device: &BluetoothDevice) -> &str {
+    if device.alias.is_empty() {
+        &device.address
+    } else {
+        &device.alias
+    }
+}
+
+impl DeviceFilter {
+    fn matches(&self, device: &BluetoothDevice) -> bool {
+        if self.hide_unnamed && device.alias.is_empty() {
+            return false;
+        }
+        if self.only_paired && !device.paired {
+            return false;
+        }
+        if let KindFilter::Kind(kind) = self.kind {
+            if DeviceKind::from_icon(&device.icon) != kind {
+                return false;
+            }
+        }
+        if let SignalFilter::Minimum(rssi) = self.signal {
+            // a RSSI of 0 means the device is not in range
+            if !device.connected && (device.rssi == 0 || device.rssi < rssi) {
+                return false;
+            }
+        }
+        let search = self.search.trim().to_lowercase();
+        search.is_empty()
+            || device.alias.to_lowercase().contains(&search)
+            || device.address.to_lowercase().contains(&search)
+    }
+
+    /// Filters and sorts the devices, `last_connected` holds the unix time
+    /// each address was last connected or disconnected
+    pub fn apply<'a>(
+        &self,
+        devices: impl Iterator<Item = &'a BluetoothDevice>,
+        last_connected: &HashMap<String, u64>,
+    ) -> Vec<&'a BluetoothDevice> {
+        let mut devices: Vec<&BluetoothDevice> =
+            devices.filter(|device| self.matches(device)).collect();
+        let by_name = |a: &&BluetoothDevice, b: &&BluetoothDevice| {
+            display_name(a)
+                .to_lowercase()
+                .cmp(&display_name(b).to_lowercase())
+        };
+        match self.sort {
+            DeviceSort::Name => devices.sort_by(by_name),
+            DeviceSort::Signal => devices.sort_by(|a, b| {
+                // unknown signal strength goes last
+                let rssi = |device: &BluetoothDevice| {
+                    if device.rssi == 0 {
+                        i16::MIN
+                    } else {
+                        device.rssi
+                    }
+                };
+                rssi(b).cmp(&rssi(a)).then_with(|| by_name(a, b))
+            }),
+            DeviceSort::LastConnected => devices.sort_by(|a, b| {
+                last_connected
+                    .get(&b.address)
+                    .cmp(&last_connected.get(&a.address))
+                    .then_with(|| by_name(a, b))
+            }),
+        }
+        devices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(alias: &str, address: &str, rssi: i16) -> BluetoothDevice {
+        BluetoothDevice {
+            alias: alias.to_string(),
+            address: address.to_string(),
+            rssi,
+            ..Default::default()
+        }
+    }
+
+    fn names(devices: Vec<&BluetoothDevice>) -> Vec<&str> {
+        devices.into_iter().map(display_name).collect()
+    }
+
+    #[test]
+    fn hides_unnamed_devices() {
+        let devices = [
+            device("Speaker", "00:00:00:00:00:01", -50),
+            device("", "00:00:00:00:00:02", -40),
+        ];
+        let filter = DeviceFilter::default();
+        assert_eq!(
+            names(filter.apply(devices.iter(), &HashMap::new())),
+            ["Speaker"]
+        );
+        let filter = DeviceFilter {
+            hide_unnamed: false,
+            ..Default::default()
+        };
+        // unnamed devices are listed by their address
+        assert_eq!(
+            names(filter.apply(devices.iter(), &HashMap::new())),
+            ["00:00:00:00:00:02", "Speaker"]
+        );
+    }
+
+    #[test]
+    fn filters_by_search_and_pairing() {
+        let mut paired = device("Headset", "AA:00:00:00:00:01", -60);
+        paired.paired = true;
+        let devices = [paired, device("Keyboard", "BB:00:00:00:00:02", -60)];
+        let filter = DeviceFilter {
+            search: " bb:00 ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            names(filter.apply(devices.iter(), &HashMap::new())),
+            ["Keyboard"]
+        );
+        let filter = DeviceFilter {
+            only_paired: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            names(filter.apply(devices.iter(), &HashMap::new())),
+            ["Headset"]
+        );
+    }
+
+    #[test]
+    fn sorts_devices() {
+        let devices = [
+            device("beta", "00:00:00:00:00:01", 0),
+            device("Alpha", "00:00:00:00:00:02", -80),
+            device("gamma", "00:00:00:00:00:03", -40),
+        ];
+        let mut filter = DeviceFilter::default();
+        assert_eq!(
+            names(filter.apply(devices.iter(), &HashMap::new())),
+            ["Alpha", "beta", "gamma"]
+        );
+
+        // unknown signal strength goes last
+        filter.sort = DeviceSort::Signal;
+        assert_eq!(
+            names(filter.apply(devices.iter(), &HashMap::new())),
+            ["gamma", "Alpha", "beta"]
+        );
+
+        // never connected devices go last
+        filter.sort = DeviceSort::LastConnected;
+        let last_connected = HashMap::from([
+            ("00:00:00:00:00:01".to_string(), 100),
+            ("00:00:00:00:00:03".to_string(), 200),
+        ]);
+        assert_eq!(
+            names(filter.apply(devices.iter(), &last_connected)),
+            ["gamma", "beta", "Alpha"]
+        );
+    }
+}
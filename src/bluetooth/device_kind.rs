@@ -0,0 +1,110 @@
+use std::fmt::Display;
+
+use crate::components::icons::Icon;
+
+/// Kind of a device, derived from the icon name BlueZ assigns based on the device class
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind {
+    Computer,
+    Phone,
+    Headset,
+    Headphones,
+    Speaker,
+    Keyboard,
+    Mouse,
+    Gamepad,
+    Tablet,
+    Printer,
+    Camera,
+    Display,
+    Network,
+    MediaPlayer,
+    Other,
+}
+
+impl DeviceKind {
+    pub const ALL: [DeviceKind; 15] = [
+        Self::Computer,
+        Self::Phone,
+        Self::Headset,
+        Self::Headphones,
+        Self::Speaker,
+        Self::Keyboard,
+        Self::Mouse,
+        Self::Gamepad,
+        Self::Tablet,
+        Self::Printer,
+        Self::Camera,
+        Self::Display,
+        Self::Network,
+        Self::MediaPlayer,
+        Self::Other,
+    ];
+
+    pub fn from_icon(icon: &str) -> Self {
+        match icon {
+            "computer" => Self::Computer,
+            "phone" => Self::Phone,
+            "audio-headset" => Self::Headset,
+            "audio-headphones" => Self::Headphones,
+            "audio-card" | "audio-speakers" => Self::Speaker,
+            "input-keyboard" => Self::Keyboard,
+            "input-mouse" => Self::Mouse,
+            "input-gaming" => Self::Gamepad,
+            "input-tablet" => Self::Tablet,
+            "printer" | "scanner" => Self::Printer,
+            "camera-photo" | "camera-video" => Self::Camera,
+            "video-display" => Self::Display,
+            "modem" | "network-wireless" => Self::Network,
+            "multimedia-player" => Self::MediaPlayer,
+            _ => Self::Other,
+        }
+    }
+
+    /// Unknown kinds fall back to the generic Bluetooth icon
+    pub fn icon(&self) -> Icon {
+        match self {
+            Self::Computer => Icon::DeviceComputer,
+            Self::Phone => Icon::DevicePhone,
+            Self::Headset => Icon::DeviceHeadset,
+            Self::Headphones => Icon::DeviceHeadphones,
+            Self::Speaker => Icon::DeviceSpeaker,
+            Self::Keyboard => Icon::DeviceKeyboard,
+            Self::Mouse => Icon::DeviceMouse,
+            Self::Gamepad => Icon::DeviceGamepad,
+            Self::Tablet => Icon::DeviceTablet,
+            Self::Printer => Icon::DevicePrinter,
+            Self::Camera => Icon::DeviceCamera,
+            Self::Display => Icon::DeviceDisplay,
+            Self::Network => Icon::DeviceNetwork,
+            Self::MediaPlayer => Icon::DeviceMediaPlayer,
+            Self::Other => Icon::Bluetooth,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Computer => "Computer",
+            Self::Phone => "Phone",
+            Self::Headset => "Headset",
+            Self::Headphones => "Headphones",
+            Self::Speaker => "Speaker",
+            Self::Keyboard => "Keyboard",
+            Self::Mouse => "Mouse",
+            Self::Gamepad => "Gamepad",
+            Self::Tablet => "Tablet",
+            Self::Printer => "Printer",
+            Self::Camera => "Camera",
+            Self::Display => "Display",
+            Self::Network => "Network",
+            Self::MediaPlayer => "Media player",
+            Self::Other => "Other",
+        }
+    }
+}
+
+impl Display for DeviceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    time::Instant,
+};
+
+use zbus::zvariant::OwnedObjectPath;
+
+use super::dbus_interface::{GattCharacteristic, GattService};
+
+/// Older notifications are dropped from the log
+const MAX_LOG_ENTRIES: usize = 200;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValueFormat {
+    #[default]
+    Hex,
+    Utf8,
+    /// Little endian unsigned integer, as used by most GATT characteristics
+    Int,
+}
+
+impl ValueFormat {
+    pub const ALL: [ValueFormat; 3] = [Self::Hex, Self::Utf8, Self::Int];
+}
+
+impl Display for ValueFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Hex => "Hex",
+            Self::Utf8 => "UTF-8",
+            Self::Int => "Integer",
+        })
+    }
+}
+
+pub fn format_value(value: &[u8], format: ValueFormat) -> String {
+    match format {
+        ValueFormat::Hex => value
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<Vec<String>>()
+            .join(" "),
+        ValueFormat::Utf8 => String::from_utf8_lossy(value).to_string(),
+        ValueFormat::Int if value.is_empty() || value.len() > 8 => {
+            "Not an integer of up to 8 bytes".to_string()
+        }
+        ValueFormat::Int => {
+            let mut bytes = [0; 8];
+            bytes[..value.len()].copy_from_slice(value);
+            u64::from_le_bytes(bytes).to_string()
+        }
+    }
+}
+
+/// Parses user input, integers use the width of the current value if it has one
+pub fn parse_value(input: &str, format: ValueFormat, width: usize) -> Result<Vec<u8>, String> {
+    match format {
+        ValueFormat::Hex => {
+            let digits: String = input
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>()
+                .trim_start_matches("0x")
+                .to_string();
+            if let Some(invalid) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+                return Err(format!("{} is not a hex digit", invalid));
+            }
+            if digits.is_empty() || !digits.len().is_multiple_of(2) {
+                return Err("Hex values need two digits per byte".to_string());
+            }
+            // only ASCII is left, so every digit is a single byte
+            Ok((0..digits.len())
+                .step_by(2)
+                .filter_map(|index| u8::from_str_radix(&digits[index..index + 2], 16).ok())
+                .collect())
+        }
+        ValueFormat::Utf8 => Ok(input.as_bytes().to_vec()),
+        ValueFormat::Int => {
+            let value: u64 = input
+                .trim()
+                .parse()
+                .map_err(|_| format!("{} is not a positive integer", input.trim()))?;
+            let needed = (8 - value.leading_zeros() as usize / 8).max(1);
+            let width = if (1..=8).contains(&width) {
+                width
+            } else {
+                needed
+            };
+            if needed > width {
+                return Err(format!("{} does not fit into {} bytes", value, width));
+            }
+            Ok(value.to_le_bytes()[..width].to_vec())
+        }
+    }
+}
+
+pub fn has_flag(characteristic: &GattCharacteristic, flag: &str) -> bool {
+    characteristic.flags.iter().any(|value| value == flag)
+}
+
+pub fn can_write(characteristic: &GattCharacteristic) -> bool {
+    has_flag(characteristic, "write") || has_flag(characteristic, "write-without-response")
+}
+
+pub fn can_notify(characteristic: &GattCharacteristic) -> bool {
+    has_flag(characteristic, "notify") || has_flag(characteristic, "indicate")
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationEntry {
+    /// Seconds since the inspector was opened
+    pub time: f32,
+    pub characteristic: OwnedObjectPath,
+    pub value: Vec<u8>,
+}
+
+/// State of the GATT inspector page of one device
+#[derive(Debug, Clone)]
+pub struct GattInspector {
+    pub services: Vec<GattService>,
+    pub selected: Option<OwnedObjectPath>,
+    /// Last read or notified value of each characteristic
+    pub values: HashMap<OwnedObjectPath, Vec<u8>>,
+    pub format: ValueFormat,
+    pub write_input: String,
+    pub subscribed: HashSet<OwnedObjectPath>,
+    pub log: VecDeque<NotificationEntry>,
+    pub error: Option<String>,
+    opened: Instant,
+}
+
+impl GattInspector {
+    pub fn new(services: Vec<GattService>) -> Self {
+        Self {
+            services,
+            selected: None,
+            values: HashMap::new(),
+            format: ValueFormat::default(),
+            write_input: String::new(),
+            subscribed: HashSet::new(),
+            log: VecDeque::new(),
+            error: None,
+            opened: Instant::now(),
+        }
+    }
+
+    pub fn characteristic(&self, path: &OwnedObjectPath) -> Option<&GattCharacteristic> {
+        self.services
+            .iter()
+            .flat_map(|service| service.characteristics.iter())
+            .find(|characteristic| characteristic.path == *path)
+    }
+
+    pub fn selected_characteristic(&self) -> Option<&GattCharacteristic> {
+        self.characteristic(self.selected.as_ref()?)
+    }
+
+    pub fn push_notification(&mut self, characteristic: OwnedObjectPath, value: Vec<u8>) {
+        if self.log.len() == MAX_LOG_ENTRIES {
+            self.log.pop_front();
+        }
+        self.log.push_back(NotificationEntry {
+            time: self.opened.elapsed().as_secs_f32(),
+            characteristic: characteristic.clone(),
+            value: value.clone(),
+        });
+        self.values.insert(characteristic, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_values() {
+        assert_eq!(format_value(&[], ValueFormat::Hex), "");
+        assert_eq!(format_value(&[0x0a, 0xff], ValueFormat::Hex), "0a ff");
+        assert_eq!(format_value(b"hi", ValueFormat::Utf8), "hi");
+        assert_eq!(format_value(&[0x34, 0x12], ValueFormat::Int), "4660");
+        assert_eq!(
+            format_value(&[], ValueFormat::Int),
+            "Not an integer of up to 8 bytes"
+        );
+        assert_eq!(
+            format_value(&[0; 9], ValueFormat::Int),
+            "Not an integer of up to 8 bytes"
+        );
+    }
+
+    #[test]
+    fn parses_hex() {
+        assert_eq!(
+            parse_value("0a ff", ValueFormat::Hex, 0),
+            Ok(vec![0x0a, 0xff])
+        );
+        assert_eq!(
+            parse_value("0x0aff", ValueFormat::Hex, 0),
+            Ok(vec![0x0a, 0xff])
+        );
+        assert!(parse_value("", ValueFormat::Hex, 0).is_err());
+        assert!(parse_value("0x", ValueFormat::Hex, 0).is_err());
+        assert!(parse_value("abc", ValueFormat::Hex, 0).is_err());
+        assert!(parse_value("zz", ValueFormat::Hex, 0).is_err());
+    }
+
+    #[test]
+    fn rejects_non_ascii_hex() {
+        assert!(parse_value("aéb", ValueFormat::Hex, 0).is_err());
+        assert!(parse_value("éé", ValueFormat::Hex, 0).is_err());
+    }
+
+    #[test]
+    fn parses_utf8() {
+        assert_eq!(parse_value("", ValueFormat::Utf8, 0), Ok(Vec::new()));
+        assert_eq!(parse_value("é", ValueFormat::Utf8, 0), Ok(vec![0xc3, 0xa9]));
+    }
+
+    #[test]
+    fn parses_integers() {
+        assert_eq!(
+            parse_value("4660", ValueFormat::Int, 0),
+            Ok(vec![0x34, 0x12])
+        );
+        assert_eq!(parse_value("0", ValueFormat::Int, 0), Ok(vec![0]));
+        assert_eq!(parse_value("1", ValueFormat::Int, 4), Ok(vec![1, 0, 0, 0]));
+        assert!(parse_value("256", ValueFormat::Int, 1).is_err());
+        assert!(parse_value("", ValueFormat::Int, 0).is_err());
+        assert!(parse_value("-1", ValueFormat::Int, 0).is_err());
+        assert!(parse_value("١", ValueFormat::Int, 0).is_err());
+    }
+}
@@ -1,3 +1,13 @@
+pub mod adapter_state;
+pub mod agent;
+pub mod audio_profile;
 pub mod bluetooth_card;
 pub mod bluetooth_impl;
+pub mod connection_error;
 pub mod dbus_interface;
+pub mod device_filter;
+pub mod device_kind;
+pub mod gatt;
+pub mod obex;
+pub mod signal;
+pub mod uuid;
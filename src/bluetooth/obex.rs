@@ -0,0 +1,103 @@
+use zbus::zvariant::OwnedObjectPath;
+
+use super::uuid::short_uuid;
+
+/// Status of an OBEX transfer as reported by org.bluez.obex.Transfer1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferStatus {
+    Queued,
+    Active,
+    Suspended,
+    Complete,
+    Error,
+}
+
+impl From<&str> for TransferStatus {
+    fn from(status: &str) -> Self {
+        match status {
+            "queued" => Self::Queued,
+            "active" => Self::Active,
+            "suspended" => Self::Suspended,
+            "complete" => Self::Complete,
+            _ => Self::Error,
+        }
+    }
+}
+
+impl TransferStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Complete | Self::Error)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub path: OwnedObjectPath,
+    pub device: OwnedObjectPath,
+    pub name: String,
+    /// Size in bytes, 0 if unknown
+    pub size: u64,
+    pub transferred: u64,
+    pub status: TransferStatus,
+    /// Received from the device instead of sent to it
+    pub incoming: bool,
+}
+
+impl Transfer {
+    /// Progress in percent
+    pub fn progress(&self) -> f32 {
+        match self.status {
+            TransferStatus::Complete => 100.0,
+            _ if self.size == 0 => 0.0,
+            _ => (self.transferred as f32 / self.size as f32 * 100.0).min(100.0),
+        }
+    }
+}
+
+/// A file a device wants to send, waiting for the user to accept or reject it
+#[derive(Debug, Clone)]
+pub struct IncomingTransfer {
+    pub transfer: OwnedObjectPath,
+    pub device: OwnedObjectPath,
+    pub name: String,
+    pub size: u64,
+}
+
+impl From<IncomingTransfer> for Transfer {
+    fn from(incoming: IncomingTransfer) -> Self {
+        Self {
+            path: incoming.transfer,
+            device: incoming.device,
+            name: incoming.name,
+            size: incoming.size,
+            transferred: 0,
+            status: TransferStatus::Queued,
+            incoming: true,
+        }
+    }
+}
+
+/// Assigned number of the OBEX Object Push profile
+const OBJECT_PUSH: u16 = 0x1105;
+
+/// Only devices that announce an Object Push server are offered to send files to
+pub fn supports_object_push(profiles: &[String]) -> bool {
+    profiles
+        .iter()
+        .any(|uuid| short_uuid(uuid) == Some(OBJECT_PUSH))
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
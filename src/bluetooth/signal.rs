@@ -0,0 +1,109 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use iced::{mouse, widget::canvas, Point, Rectangle, Renderer, Theme};
+
+use crate::components::icons::Icon;
+
+/// Time span shown in the graph, older samples are dropped
+pub const RSSI_WINDOW: Duration = Duration::from_secs(60);
+/// Range of the graph in dBm
+const RSSI_MIN: f32 = -100.0;
+const RSSI_MAX: f32 = -30.0;
+
+/// BlueZ reports 0 when the device is not in range
+pub fn signal_icon(rssi: i16) -> Icon {
+    match rssi {
+        0 => Icon::SignalNone,
+        rssi if rssi >= -60 => Icon::Signal4Bar,
+        rssi if rssi >= -70 => Icon::Signal3Bar,
+        rssi if rssi >= -80 => Icon::Signal2Bar,
+        _ => Icon::Signal1Bar,
+    }
+}
+
+/// Signal strength of a device over the last minute
+#[derive(Debug, Clone, Default)]
+pub struct RssiHistory {
+    samples: VecDeque<(Instant, i16)>,
+}
+
+impl RssiHistory {
+    pub fn push(&mut self, rssi: i16) {
+        let now = Instant::now();
+        while self
+            .samples
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > RSSI_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+        if rssi != 0 {
+            self.samples.push_back((now, rssi));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+fn rssi_to_y(rssi: i16) -> f32 {
+    1.0 - ((rssi as f32 - RSSI_MIN) / (RSSI_MAX - RSSI_MIN)).clamp(0.0, 1.0)
+}
+
+pub struct RssiGraph<'a> {
+    pub history: &'a RssiHistory,
+}
+
+impl<Message> canvas::Program<Message> for RssiGraph<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let palette = theme.extended_palette();
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let (width, height) = (frame.width(), frame.height());
+
+        // guides at -90, -70 and -50 dBm
+        for rssi in [-90, -70, -50] {
+            let y = rssi_to_y(rssi) * height;
+            frame.stroke(
+                &canvas::Path::line(Point::new(0.0, y), Point::new(width, y)),
+                canvas::Stroke::default()
+                    .with_color(palette.background.strong.color)
+                    .with_width(1.0),
+            );
+        }
+
+        let now = Instant::now();
+        let window = RSSI_WINDOW.as_secs_f32();
+        let path = canvas::Path::new(|builder| {
+            for (index, (time, rssi)) in self.history.samples.iter().enumerate() {
+                let age = now.duration_since(*time).as_secs_f32().min(window);
+                let point = Point::new((1.0 - age / window) * width, rssi_to_y(*rssi) * height);
+                if index == 0 {
+                    builder.move_to(point);
+                } else {
+                    builder.line_to(point);
+                }
+            }
+        });
+        frame.stroke(
+            &path,
+            canvas::Stroke::default()
+                .with_color(palette.primary.base.color)
+                .with_width(2.0),
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
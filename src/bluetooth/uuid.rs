@@ -0,0 +1,79 @@
+/// Suffix shared by all UUIDs derived from a 16 bit Bluetooth SIG assigned number
+const BASE_UUID_SUFFIX: &str = "-0000-1000-8000-00805f9b34fb";
+
+const WELL_KNOWN_UUIDS: [(u16, &str); 49] = [
+    (0x1101, "Serial Port"),
+    (0x1105, "Object Push"),
+    (0x1106, "File Transfer"),
+    (0x1108, "Headset"),
+    (0x110a, "Audio Source"),
+    (0x110b, "Audio Sink"),
+    (0x110c, "Remote Control Target"),
+    (0x110e, "Remote Control"),
+    (0x1112, "Headset Audio Gateway"),
+    (0x1115, "Personal Area Network"),
+    (0x1116, "Network Access Point"),
+    (0x111e, "Handsfree"),
+    (0x111f, "Handsfree Audio Gateway"),
+    (0x1124, "Human Interface Device"),
+    (0x112f, "Phonebook Access"),
+    (0x1132, "Message Access"),
+    (0x1200, "PnP Information"),
+    (0x1800, "Generic Access"),
+    (0x1801, "Generic Attribute"),
+    (0x180a, "Device Information"),
+    (0x180f, "Battery Service"),
+    (0x1812, "Human Interface Device over GATT"),
+    (0x1805, "Current Time"),
+    (0x1809, "Health Thermometer"),
+    (0x180d, "Heart Rate"),
+    (0x1816, "Cycling Speed and Cadence"),
+    (0x181a, "Environmental Sensing"),
+    // GATT characteristics
+    (0x2a00, "Device Name"),
+    (0x2a01, "Appearance"),
+    (0x2a04, "Peripheral Preferred Connection Parameters"),
+    (0x2a05, "Service Changed"),
+    (0x2a19, "Battery Level"),
+    (0x2a23, "System ID"),
+    (0x2a24, "Model Number"),
+    (0x2a25, "Serial Number"),
+    (0x2a26, "Firmware Revision"),
+    (0x2a27, "Hardware Revision"),
+    (0x2a28, "Software Revision"),
+    (0x2a29, "Manufacturer Name"),
+    (0x2a37, "Heart Rate Measurement"),
+    (0x2a38, "Body Sensor Location"),
+    (0x2a4a, "HID Information"),
+    (0x2a4b, "Report Map"),
+    (0x2a4d, "Report"),
+    (0x2a50, "PnP ID"),
+    (0x2a6e, "Temperature"),
+    (0x2a6f, "Humidity"),
+    (0x2b29, "Client Supported Features"),
+    (0x2b2a, "Database Hash"),
+];
+
+/// Returns the 16 bit assigned number if the UUID is based on the Bluetooth base UUID
+pub fn short_uuid(uuid: &str) -> Option<u16> {
+    let uuid = uuid.to_lowercase();
+    let prefix = uuid.strip_suffix(BASE_UUID_SUFFIX)?;
+    let value = u32::from_str_radix(prefix, 16).ok()?;
+    u16::try_from(value).ok()
+}
+
+pub fn uuid_name(uuid: &str) -> Option<&'static str> {
+    let short = short_uuid(uuid)?;
+    WELL_KNOWN_UUIDS
+        .iter()
+        .find(|(number, _)| *number == short)
+        .map(|(_, name)| *name)
+}
+
+/// Well known name of the UUID, or the UUID itself
+pub fn uuid_label(uuid: &str) -> String {
+    match uuid_name(uuid) {
+        Some(name) => name.to_string(),
+        None => uuid.to_string(),
+    }
+}
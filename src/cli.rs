@@ -0,0 +1,303 @@
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use serde_json::json;
+use zbus::Connection;
+
+use crate::{
+    audio::dbus_interface::{AudioDbusProxy, AudioSink},
+    bluetooth::{
+        connection_error::{connection_error_reason, connection_result, CONNECT_TIMEOUT},
+        dbus_interface::{BluetoothDbusProxy, BluetoothDevice},
+        device_filter::display_name,
+    },
+    utils::{daemon_error_message, ignore},
+    PageId,
+};
+
+/// Volume the daemon treats as 100%
+const FULL_VOLUME: u32 = 65536;
+/// Same limit as the volume sliders, roughly 153%
+const MAX_VOLUME: u32 = 100_270;
+
+/// ReSet settings, opens the window when no command is given
+#[derive(Parser)]
+#[command(name = "reset", version)]
+pub struct Cli {
+    /// Prints the result as JSON for scripts
+    #[arg(long, global = true)]
+    pub json: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Changes audio devices without opening the window
+    #[command(subcommand)]
+    Audio(AudioCommand),
+    /// Connects and discovers Bluetooth devices without opening the window
+    #[command(subcommand)]
+    Bluetooth(BluetoothCommand),
+    /// Opens the window on a page
+    Page { page: PageId },
+}
+
+#[derive(Subcommand)]
+pub enum AudioCommand {
+    /// Lists all output devices, the default one is marked with *
+    ListSinks,
+    /// Makes a sink the default output, given by name or index
+    SetDefault { sink: String },
+    /// Sets the volume of a sink given by name or index, e.g. 50%
+    Volume {
+        sink: String,
+        #[arg(value_parser = parse_volume)]
+        volume: u32,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BluetoothCommand {
+    /// Connects to a known device by its address
+    Connect { address: String },
+    /// Searches for devices and lists everything found afterwards
+    Scan {
+        /// Duration of the scan in seconds
+        #[arg(long, default_value_t = 10)]
+        timeout: u64,
+    },
+}
+
+/// Accepts percentages with or without the % sign
+fn parse_volume(input: &str) -> Result<u32, String> {
+    let percentage: u32 = input
+        .trim()
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| format!("{} is not a percentage", input))?;
+    let volume = percentage as u64 * FULL_VOLUME as u64 / 100;
+    if volume > MAX_VOLUME as u64 {
+        return Err(format!(
+            "The volume can be at most {}%",
+            MAX_VOLUME * 100 / FULL_VOLUME
+        ));
+    }
+    Ok(volume as u32)
+}
+
+fn volume_percentage(sink: &AudioSink) -> u32 {
+    let volume = sink.volume.first().copied().unwrap_or(0);
+    (volume as f32 / FULL_VOLUME as f32 * 100.0).round() as u32
+}
+
+/// Sinks are matched by name first, scripts may use the index as well
+fn find_sink(sinks: Vec<AudioSink>, sink: &str) -> Result<AudioSink, zbus::Error> {
+    let index = sink.parse::<u32>().ok();
+    sinks
+        .into_iter()
+        .find(|value| value.name == sink || Some(value.index) == index)
+        .ok_or_else(|| zbus::Error::Failure(format!("There is no sink {}", sink)))
+}
+
+fn sink_json(sink: &AudioSink, default_sink: &str) -> serde_json::Value {
+    json!({
+        "index": sink.index,
+        "name": sink.name,
+        "alias": sink.alias,
+        "volume": volume_percentage(sink),
+        "muted": sink.muted,
+        "default": sink.name == default_sink,
+    })
+}
+
+fn device_json(device: &BluetoothDevice) -> serde_json::Value {
+    json!({
+        "address": device.address,
+        "name": display_name(device),
+        "rssi": device.rssi,
+        "paired": device.paired,
+        "connected": device.connected,
+    })
+}
+
+async fn run_audio(command: AudioCommand, json: bool) -> Result<(), zbus::Error> {
+    let conn = Connection::session().await?;
+    let proxy = AudioDbusProxy::new(&conn).await?;
+    match command {
+        AudioCommand::ListSinks => {
+            let sinks = proxy.list_sinks().await?;
+            let default_sink = proxy.get_default_sink_name().await?;
+            if json {
+                let sinks: Vec<serde_json::Value> = sinks
+                    .iter()
+                    .map(|sink| sink_json(sink, &default_sink))
+                    .collect();
+                println!("{}", json!(sinks));
+            } else {
+                for sink in sinks {
+                    println!(
+                        "{} {:>3}  {}  {}%{}  {}",
+                        if sink.name == default_sink { "*" } else { " " },
+                        sink.index,
+                        sink.alias,
+                        volume_percentage(&sink),
+                        if sink.muted { " (muted)" } else { "" },
+                        sink.name
+                    );
+                }
+            }
+        }
+        AudioCommand::SetDefault { sink } => {
+            let sink = find_sink(proxy.list_sinks().await?, &sink)?;
+            let sink = proxy.set_default_sink(sink.name).await?;
+            if json {
+                println!("{}", sink_json(&sink, &sink.name));
+            } else {
+                println!("Default output is now {}", sink.alias);
+            }
+        }
+        AudioCommand::Volume { sink, volume } => {
+            let mut sink = find_sink(proxy.list_sinks().await?, &sink)?;
+            proxy
+                .set_sink_volume(sink.index, sink.channels, volume)
+                .await?;
+            sink.volume = vec![volume; sink.channels as usize];
+            if json {
+                let default_sink = proxy.get_default_sink_name().await?;
+                println!("{}", sink_json(&sink, &default_sink));
+            } else {
+                println!(
+                    "Volume of {} is now {}%",
+                    sink.alias,
+                    volume_percentage(&sink)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_bluetooth(command: BluetoothCommand, json: bool) -> Result<(), zbus::Error> {
+    let conn = Connection::session().await?;
+    let proxy = BluetoothDbusProxy::new(&conn).await?;
+    match command {
+        BluetoothCommand::Connect { address } => {
+            let mut device = proxy
+                .get_bluetooth_devices()
+                .await?
+                .into_iter()
+                .find(|device| device.address.eq_ignore_ascii_case(&address))
+                .ok_or_else(|| {
+                    zbus::Error::Failure(format!("There is no known device {}", address))
+                })?;
+            // the daemon only answers once BlueZ gave up or succeeded
+            let connect = proxy.connect_to_bluetooth_device(device.path.clone());
+            let result = match tokio::time::timeout(CONNECT_TIMEOUT, connect).await {
+                Ok(result) => connection_result(result),
+                Err(_) => {
+                    // BlueZ keeps connecting after the call is dropped
+                    ignore(
+                        proxy
+                            .disconnect_from_bluetooth_device(device.path.clone())
+                            .await,
+                    );
+                    Err(connection_error_reason("timeout"))
+                }
+            };
+            if let Err(reason) = result {
+                return Err(zbus::Error::Failure(format!(
+                    "Could not connect to {}: {}",
+                    display_name(&device),
+                    reason
+                )));
+            }
+            device.connected = true;
+            if json {
+                println!("{}", device_json(&device));
+            } else {
+                println!("Connected to {}", display_name(&device));
+            }
+        }
+        BluetoothCommand::Scan { timeout } => {
+            proxy.start_bluetooth_scan().await?;
+            tokio::time::sleep(Duration::from_secs(timeout)).await;
+            let devices = proxy.get_bluetooth_devices().await;
+            // the discovery is stopped even if the devices could not be listed
+            proxy.stop_bluetooth_scan().await?;
+            let mut devices = devices?;
+            // devices in range first, strongest signal first
+            devices.sort_by_key(|device| match device.rssi {
+                0 => i16::MAX,
+                rssi => -rssi,
+            });
+            if json {
+                let devices: Vec<serde_json::Value> = devices.iter().map(device_json).collect();
+                println!("{}", json!(devices));
+            } else {
+                for device in devices {
+                    let signal = match device.rssi {
+                        0 => "out of range".to_string(),
+                        rssi => format!("{} dBm", rssi),
+                    };
+                    println!(
+                        "{}  {}  {}{}{}",
+                        device.address,
+                        display_name(&device),
+                        signal,
+                        if device.paired { ", paired" } else { "" },
+                        if device.connected { ", connected" } else { "" }
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs a command without a window, returns the exit code
+pub async fn run(command: Command, json: bool) -> i32 {
+    let result = match command {
+        Command::Audio(command) => run_audio(command, json).await,
+        Command::Bluetooth(command) => run_bluetooth(command, json).await,
+        Command::Page { .. } => Ok(()),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            let message = daemon_error_message(error);
+            if json {
+                eprintln!("{}", json!({ "error": message }));
+            } else {
+                eprintln!("{}", message);
+            }
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_percentages() {
+        assert_eq!(parse_volume("50%"), Ok(FULL_VOLUME / 2));
+        assert_eq!(parse_volume("50"), Ok(FULL_VOLUME / 2));
+        assert_eq!(parse_volume(" 100% "), Ok(FULL_VOLUME));
+        assert_eq!(parse_volume("0"), Ok(0));
+    }
+
+    #[test]
+    fn rejects_volumes_above_the_limit() {
+        assert_eq!(parse_volume("153%"), Ok(153 * FULL_VOLUME / 100));
+        assert!(parse_volume("154%").is_err());
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(parse_volume("loud").is_err());
+        assert!(parse_volume("").is_err());
+        assert!(parse_volume("-10%").is_err());
+    }
+}
@@ -3,7 +3,7 @@ use std::{borrow::Borrow, collections::HashMap, ops::RangeInclusive};
 use iced::{
     alignment::{Horizontal, Vertical},
     border,
-    widget::{column, container::Style, row, text, Button, Slider},
+    widget::{column, container::Style, row, text, Button, Row, Slider},
     Element, Length, Theme,
 };
 use oxiced::widgets::{
@@ -12,7 +12,11 @@ use oxiced::widgets::{
 };
 
 use crate::{
-    audio::{audio_impl::AudioMsg, dbus_interface::TAudioObject},
+    audio::{
+        audio_impl::AudioMsg,
+        dbus_interface::TAudioObject,
+        speaker_test::{channel_layout, SpeakerTest},
+    },
     ReSetMessage,
 };
 
@@ -30,6 +34,12 @@ pub trait TCardUser {
     fn muted_icon() -> Icon;
     fn unmuted_icon() -> Icon;
     fn title() -> String;
+    fn speaker_test<'a>(
+        &self,
+        _speaker_test: Option<SpeakerTest>,
+    ) -> Option<Element<'a, ReSetMessage>> {
+        None
+    }
 }
 
 pub trait TStreamCardUser<C> {
@@ -201,6 +211,7 @@ where
 pub fn device_card_view<T>(
     default_index: u32,
     object_map: &HashMap<u32, T>,
+    speaker_test: Option<SpeakerTest>,
 ) -> Element<'_, ReSetMessage>
 where
     T: Clone + ToString + PartialEq,
@@ -231,6 +242,7 @@ where
             move |value| wrap(T::volume_fn(index, channels, value)),
         );
         AudioDeviceCard::new(mute_button, slider, radio, object.name())
+            .speaker_test(object.speaker_test(speaker_test))
     };
 
     let cards: Vec<Element<ReSetMessage>> = objects
@@ -248,6 +260,49 @@ where
     .into()
 }
 
+pub fn speaker_test_view<'a>(
+    index: u32,
+    channels: u16,
+    speaker_test: Option<SpeakerTest>,
+) -> Element<'a, ReSetMessage> {
+    let active_test = speaker_test.filter(|test| test.sink_index == index);
+    let channel_buttons: Vec<Element<ReSetMessage>> = channel_layout(channels)
+        .into_iter()
+        .map(|channel| {
+            let variant = if active_test.is_some_and(|test| test.channel == channel) {
+                ButtonVariant::Primary
+            } else {
+                ButtonVariant::RowEntry
+            };
+            button(text(channel.label()), variant)
+                .on_press(wrap(AudioMsg::TestSinkChannel(index, channel)))
+                .into()
+        })
+        .collect();
+    let (status, toggle_title, toggle_msg) = match active_test {
+        Some(test) => (
+            format!("Playing: {}", test.channel.label()),
+            "Stop",
+            AudioMsg::StopSinkTest,
+        ),
+        None => (
+            "Speaker test".to_string(),
+            "Test all",
+            AudioMsg::TestAllSinkChannels(index),
+        ),
+    };
+    column!(
+        row!(
+            text(status).width(Length::Fill),
+            button(text(toggle_title), ButtonVariant::Primary).on_press(wrap(toggle_msg))
+        )
+        .align_y(Vertical::Center),
+        Row::with_children(channel_buttons).spacing(10).wrap()
+    )
+    .spacing(10)
+    .into()
+}
+
 pub fn stream_card_view<'a, T, C>(
     stream: T,
     object_map: &HashMap<u32, C>,
@@ -11,6 +11,7 @@ pub struct AudioDeviceCard<'a, C, Message> {
     radio: Radio<'a, Message>,
     name: String,
     slider: Slider<'a, C, Message>,
+    speaker_test: Option<Element<'a, Message>>,
 }
 
 impl<'a, C, Message> AudioDeviceCard<'a, C, Message>
@@ -29,9 +30,16 @@ where
             radio,
             name: name.into(),
             slider,
+            speaker_test: None,
         }
     }
 
+    /// Sets the speaker test shown below the volume slider.
+    pub fn speaker_test(mut self, speaker_test: Option<Element<'a, Message>>) -> Self {
+        self.speaker_test = speaker_test;
+        self
+    }
+
     pub fn view(self) -> Element<'a, Message> {
         iced::widget::container(
             column!(
@@ -43,6 +51,10 @@ where
                     .spacing(20)
                     .align_y(Vertical::Center),
             )
+            .push_maybe(
+                self.speaker_test
+                    .map(|speaker_test| iced::widget::container(speaker_test).padding(20)),
+            )
             .spacing(20)
             .align_x(Horizontal::Left),
         )
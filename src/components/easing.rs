@@ -61,29 +61,58 @@ impl Easing {
 
         sample.position().y
     }
+
+    /// Samples points at evenly spaced distances along the path,
+    /// clamped to 0,0 and 1,1 since control points may lie outside
+    pub fn points(&self, samples: usize) -> Vec<Point> {
+        let mut sampler = self
+            .measurements
+            .create_sampler(&self.path, lyon_algorithms::measure::SampleType::Normalized);
+        (0..=samples)
+            .map(|step| {
+                let position = sampler.sample(step as f32 / samples as f32).position();
+                Point::new(position.x.clamp(0.0, 1.0), position.y.clamp(0.0, 1.0))
+            })
+            .collect()
+    }
 }
 
-pub struct Builder(NoAttributes<BuilderImpl>);
+pub struct Builder {
+    path: NoAttributes<BuilderImpl>,
+    /// Points are clamped to 0,0 and 1,1, only curves that are no easings skip it
+    clamp: bool,
+}
 
 impl Builder {
     pub fn new() -> Self {
-        let mut builder = Path::builder();
-        builder.begin(lyon_algorithms::geom::point(0.0, 0.0));
+        let mut path = Path::builder();
+        path.begin(lyon_algorithms::geom::point(0.0, 0.0));
 
-        Self(builder)
+        Self { path, clamp: true }
+    }
+
+    /// Starts an unclamped path at the given point, for curves like the equalizer
+    /// response whose control points overshoot. Sample it with `Easing::points`
+    pub fn starting_at(start: impl Into<Point>) -> Self {
+        let start: Point = start.into();
+        let mut path = Path::builder();
+        path.begin(lyon_algorithms::geom::point(start.x, start.y));
+
+        Self { path, clamp: false }
     }
 
     /// Adds a line segment. Points must be between 0,0 and 1,1
     pub fn line_to(mut self, to: impl Into<Point>) -> Self {
-        self.0.line_to(Self::point(to));
+        let to = self.point(to);
+        self.path.line_to(to);
 
         self
     }
 
     /// Adds a quadratic bézier curve. Points must be between 0,0 and 1,1
     pub fn quadratic_bezier_to(mut self, ctrl: impl Into<Point>, to: impl Into<Point>) -> Self {
-        self.0
-            .quadratic_bezier_to(Self::point(ctrl), Self::point(to));
+        let (ctrl, to) = (self.point(ctrl), self.point(to));
+        self.path.quadratic_bezier_to(ctrl, to);
 
         self
     }
@@ -95,25 +124,40 @@ impl Builder {
         ctrl2: impl Into<Point>,
         to: impl Into<Point>,
     ) -> Self {
-        self.0
-            .cubic_bezier_to(Self::point(ctrl1), Self::point(ctrl2), Self::point(to));
+        let (ctrl1, ctrl2, to) = (self.point(ctrl1), self.point(ctrl2), self.point(to));
+        self.path.cubic_bezier_to(ctrl1, ctrl2, to);
 
         self
     }
 
     pub fn build(mut self) -> Easing {
-        self.0.line_to(lyon_algorithms::geom::point(1.0, 1.0));
-        self.0.end(false);
+        self.path.line_to(lyon_algorithms::geom::point(1.0, 1.0));
+        self.path.end(false);
+
+        let path = self.path.build();
+        let measurements = PathMeasurements::from_path(&path, 0.0);
+
+        Easing { path, measurements }
+    }
+
+    /// Ends the path at the last added point instead of closing it at 1,1
+    pub fn build_open(mut self) -> Easing {
+        self.path.end(false);
 
-        let path = self.0.build();
+        let path = self.path.build();
         let measurements = PathMeasurements::from_path(&path, 0.0);
 
         Easing { path, measurements }
     }
 
-    fn point(p: impl Into<Point>) -> lyon_algorithms::geom::Point<f32> {
+    /// Points outside 0,0 and 1,1 are clamped unless the path started with `starting_at`
+    fn point(&self, p: impl Into<Point>) -> lyon_algorithms::geom::Point<f32> {
         let p: Point = p.into();
-        lyon_algorithms::geom::point(p.x.clamp(0.0, 1.0), p.y.clamp(0.0, 1.0))
+        if self.clamp {
+            lyon_algorithms::geom::point(p.x.clamp(0.0, 1.0), p.y.clamp(0.0, 1.0))
+        } else {
+            lyon_algorithms::geom::point(p.x, p.y)
+        }
     }
 }
 
@@ -0,0 +1,193 @@
+use iced::{
+    alignment::{Horizontal, Vertical},
+    mouse,
+    widget::{canvas, column, row, text, vertical_slider, Row},
+    Element, Length, Point, Rectangle, Renderer, Theme,
+};
+
+use crate::{
+    audio::{
+        audio_impl::AudioMsg,
+        dbus_interface::{AudioEqualizer, AudioSink},
+        equalizer::{band_label, EQUALIZER_BANDS, EQUALIZER_PRESETS, MAX_GAIN},
+    },
+    utils::rounded_card,
+    ReSetMessage,
+};
+
+use super::{
+    comborow::{ComboPickerTitle, CustomPickList, PickerVariant},
+    easing::{Builder, Easing},
+};
+
+const CURVE_SAMPLES: usize = 200;
+
+fn wrap(audio_msg: AudioMsg) -> ReSetMessage {
+    ReSetMessage::SubMsgAudio(audio_msg)
+}
+
+/// Maps a gain in dB to the normalized height of the graph, 0 being the top
+fn gain_to_y(gain: f64) -> f32 {
+    (0.5 - gain / (2.0 * MAX_GAIN)) as f32
+}
+
+/// Smooth curve through all bands, built from catmull-rom segments
+fn response_curve(gains: &[f64]) -> Option<Easing> {
+    if gains.len() < 2 {
+        return None;
+    }
+    let last = gains.len() - 1;
+    let points: Vec<Point> = gains
+        .iter()
+        .enumerate()
+        .map(|(index, gain)| Point::new(index as f32 / last as f32, gain_to_y(*gain)))
+        .collect();
+    let mut builder = Builder::starting_at(points[0]);
+    for index in 0..last {
+        let previous = points[index.saturating_sub(1)];
+        let start = points[index];
+        let end = points[index + 1];
+        let next = points[(index + 2).min(last)];
+        builder = builder.cubic_bezier_to(
+            [
+                start.x + (end.x - previous.x) / 6.0,
+                start.y + (end.y - previous.y) / 6.0,
+            ],
+            [
+                end.x - (next.x - start.x) / 6.0,
+                end.y - (next.y - start.y) / 6.0,
+            ],
+            end,
+        );
+    }
+    Some(builder.build_open())
+}
+
+struct EqualizerGraph<'a> {
+    gains: &'a [f64],
+    enabled: bool,
+}
+
+impl<Message> canvas::Program<Message> for EqualizerGraph<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let palette = theme.extended_palette();
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let (width, height) = (frame.width(), frame.height());
+
+        let zero_line = canvas::Path::line(
+            Point::new(0.0, height / 2.0),
+            Point::new(width, height / 2.0),
+        );
+        frame.stroke(
+            &zero_line,
+            canvas::Stroke::default()
+                .with_color(palette.background.strong.color)
+                .with_width(1.0),
+        );
+
+        if let Some(curve) = response_curve(self.gains) {
+            let points = curve.points(CURVE_SAMPLES);
+            let path = canvas::Path::new(|builder| {
+                for (index, point) in points.iter().enumerate() {
+                    let point = Point::new(point.x * width, point.y * height);
+                    if index == 0 {
+                        builder.move_to(point);
+                    } else {
+                        builder.line_to(point);
+                    }
+                }
+            });
+            let color = if self.enabled {
+                palette.primary.base.color
+            } else {
+                palette.background.strong.color
+            };
+            frame.stroke(
+                &path,
+                canvas::Stroke::default().with_color(color).with_width(3.0),
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+fn band_slider<'a>(index: u32, band: usize, gain: f64) -> Element<'a, ReSetMessage> {
+    column!(
+        vertical_slider(-MAX_GAIN..=MAX_GAIN, gain, move |value| wrap(
+            AudioMsg::SetEqualizerBand(index, band, value)
+        ))
+        .step(0.5)
+        .height(150),
+        text(band_label(EQUALIZER_BANDS[band])).size(12),
+        text(format!("{:+.1}", gain)).size(12),
+    )
+    .spacing(5)
+    .width(Length::Fill)
+    .align_x(Horizontal::Center)
+    .into()
+}
+
+pub fn equalizer_view<'a>(
+    sink: &AudioSink,
+    equalizer: &'a AudioEqualizer,
+) -> Element<'a, ReSetMessage> {
+    let index = sink.index;
+    let presets: Vec<String> = EQUALIZER_PRESETS
+        .iter()
+        .map(|preset| preset.name.to_string())
+        .collect();
+    let preset_picker = CustomPickList::new(
+        PickerVariant::ComboPicker(ComboPickerTitle::new(
+            "Preset",
+            Some(equalizer.preset.clone()),
+        )),
+        presets,
+        Some(equalizer.preset.clone()),
+        move |preset| wrap(AudioMsg::SetEqualizerPreset(index, preset)),
+    );
+    let sliders: Vec<Element<ReSetMessage>> = equalizer
+        .gains
+        .iter()
+        .enumerate()
+        .map(|(band, gain)| band_slider(index, band, *gain))
+        .collect();
+    let graph = canvas(EqualizerGraph {
+        gains: &equalizer.gains,
+        enabled: equalizer.enabled,
+    })
+    .width(Length::Fill)
+    .height(200);
+
+    column!(
+        text("Equalizer").size(30),
+        iced::widget::container(
+            column!(
+                row!(
+                    text(sink.alias.clone()).width(Length::Fill),
+                    oxiced::widgets::oxi_toggler::toggler(equalizer.enabled)
+                        .on_toggle(move |value| wrap(AudioMsg::SetEqualizerEnabled(index, value)))
+                )
+                .align_y(Vertical::Center),
+                preset_picker,
+                graph,
+                Row::with_children(sliders).spacing(5),
+            )
+            .spacing(20)
+            .padding(20),
+        )
+        .style(rounded_card),
+    )
+    .padding(20)
+    .spacing(20)
+    .into()
+}
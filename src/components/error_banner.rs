@@ -0,0 +1,46 @@
+use iced::{
+    alignment::Vertical,
+    border,
+    widget::{container, row, text},
+    Element, Length, Theme,
+};
+use oxiced::widgets::oxi_button::{button, ButtonVariant};
+
+use crate::ReSetMessage;
+
+fn error_style(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+
+    container::Style {
+        background: Some(palette.danger.weak.color.into()),
+        text_color: Some(palette.danger.weak.text),
+        border: border::rounded(10),
+        ..container::Style::default()
+    }
+}
+
+pub fn error_banner(message: &str, dismiss: ReSetMessage) -> Element<'_, ReSetMessage> {
+    error_banner_with_action(message, None, dismiss)
+}
+
+/// Error banner with an additional action like a retry in front of the dismiss button
+pub fn error_banner_with_action<'a>(
+    message: &'a str,
+    action: Option<(&'a str, ReSetMessage)>,
+    dismiss: ReSetMessage,
+) -> Element<'a, ReSetMessage> {
+    container(
+        row!(text(message).width(Length::Fill))
+            .push_maybe(
+                action
+                    .map(|(title, msg)| button(text(title), ButtonVariant::RowEntry).on_press(msg)),
+            )
+            .push(button(text("Dismiss"), ButtonVariant::RowEntry).on_press(dismiss))
+            .spacing(10)
+            .align_y(Vertical::Center),
+    )
+    .padding(10)
+    .width(Length::Fill)
+    .style(error_style)
+    .into()
+}
@@ -7,6 +7,7 @@ pub enum Icon {
     Audio,
     AudioDevices,
     AudioCards,
+    Equalizer,
     MicMuted,
     Mic,
     Volume,
@@ -22,6 +23,26 @@ pub enum Icon {
     Bluetooth,
     BluetoothConnected,
     BluetoothDisabled,
+    SignalNone,
+    Signal1Bar,
+    Signal2Bar,
+    Signal3Bar,
+    Signal4Bar,
+    // Bluetooth device classes
+    DeviceComputer,
+    DevicePhone,
+    DeviceHeadset,
+    DeviceHeadphones,
+    DeviceSpeaker,
+    DeviceKeyboard,
+    DeviceMouse,
+    DeviceGamepad,
+    DeviceTablet,
+    DevicePrinter,
+    DeviceCamera,
+    DeviceDisplay,
+    DeviceNetwork,
+    DeviceMediaPlayer,
     // General
     ChevronLeft,
     ChevronRight,
@@ -2,8 +2,15 @@ pub mod audio_card;
 pub mod audio_device_card;
 pub mod comborow;
 pub mod easing;
+pub mod equalizer_card;
+pub mod error_banner;
 pub mod icons;
 pub mod loading_spinner;
+pub mod modal;
+pub mod page_error;
+pub mod page_loading;
 pub mod radio;
+pub mod reconnecting;
 pub mod select_row;
 pub mod sidebar;
+pub mod toast;
@@ -0,0 +1,29 @@
+use iced::{
+    widget::{center, container, opaque, stack},
+    Color, Element,
+};
+
+use crate::ReSetMessage;
+
+/// Shows the dialog on top of the base and blocks any interaction with the base
+pub fn modal<'a>(
+    base: impl Into<Element<'a, ReSetMessage>>,
+    dialog: impl Into<Element<'a, ReSetMessage>>,
+) -> Element<'a, ReSetMessage> {
+    stack![
+        base.into(),
+        opaque(center(opaque(dialog)).style(|_theme| {
+            container::Style {
+                background: Some(
+                    Color {
+                        a: 0.8,
+                        ..Color::BLACK
+                    }
+                    .into(),
+                ),
+                ..container::Style::default()
+            }
+        }))
+    ]
+    .into()
+}
@@ -0,0 +1,28 @@
+use iced::{
+    alignment::Horizontal,
+    widget::{column, container, text},
+    Element, Length,
+};
+use oxiced::widgets::oxi_button::{button, ButtonVariant};
+
+use crate::ReSetMessage;
+
+/// Replaces a page whose model could not be created
+pub fn page_error<'a>(
+    title: &'a str,
+    message: &'a str,
+    retry: ReSetMessage,
+) -> Element<'a, ReSetMessage> {
+    container(
+        column!(
+            text(format!("{} is unavailable", title)).size(25),
+            text(message),
+            button(text("Retry"), ButtonVariant::Primary).on_press(retry),
+        )
+        .spacing(20)
+        .align_x(Horizontal::Center),
+    )
+    .padding(40)
+    .center_x(Length::Fill)
+    .into()
+}
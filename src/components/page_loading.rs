@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use iced::{
+    alignment::Horizontal,
+    widget::{column, container, text},
+    Element, Length,
+};
+
+use crate::ReSetMessage;
+
+use super::{easing::STANDARD, loading_spinner::Circular};
+
+/// Shown instead of a page while its model lists everything from the daemon
+pub fn page_loading<'a>(title: &'a str) -> Element<'a, ReSetMessage> {
+    container(
+        column!(
+            Circular::new()
+                .easing(&STANDARD)
+                .cycle_duration(Duration::from_millis(3000)),
+            text(format!("Loading {}", title)),
+        )
+        .spacing(20)
+        .align_x(Horizontal::Center),
+    )
+    .padding(40)
+    .center_x(Length::Fill)
+    .into()
+}
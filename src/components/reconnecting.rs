@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use iced::{
+    alignment::Horizontal,
+    widget::{column, container, row, text},
+    Element,
+};
+use oxiced::widgets::oxi_button::{button, ButtonVariant};
+
+use crate::{utils::rounded_card, ReSetMessage};
+
+use super::{easing::STANDARD, loading_spinner::Circular};
+
+/// Shown on top of everything while the daemon is gone
+pub fn reconnecting<'a>() -> Element<'a, ReSetMessage> {
+    container(
+        column!(
+            Circular::new()
+                .easing(&STANDARD)
+                .cycle_duration(Duration::from_millis(3000)),
+            text("Reconnecting to the daemon").size(20),
+            text("The ReSet daemon stopped, waiting for it to come back"),
+        )
+        .spacing(20)
+        .padding(20)
+        .align_x(Horizontal::Center),
+    )
+    .style(rounded_card)
+    .into()
+}
+
+/// Shown once registering with the daemon was given up
+pub fn reconnect_failed<'a>(
+    retry: ReSetMessage,
+    dismiss: ReSetMessage,
+) -> Element<'a, ReSetMessage> {
+    container(
+        column!(
+            text("The daemon did not come back").size(20),
+            text("Settings can not be changed until the ReSet daemon runs again"),
+            row!(
+                button(text("Continue without daemon"), ButtonVariant::RowEntry).on_press(dismiss),
+                button(text("Retry"), ButtonVariant::Primary).on_press(retry),
+            )
+            .spacing(10),
+        )
+        .spacing(20)
+        .padding(20)
+        .align_x(Horizontal::Center),
+    )
+    .style(rounded_card)
+    .into()
+}
@@ -4,7 +4,7 @@ use iced::{
     color,
     widget::{
         button::{Status, Style},
-        container, row,
+        container, row, tooltip,
     }, Border, Element, Length, Padding, Shadow, Theme, Vector,
 };
 use oxiced::widgets::common::{darken_color, lighten_color};
@@ -23,6 +23,7 @@ pub struct EntryButton {
     pub icon: Option<Icon>,
     pub msg: ReSetMessage,
     pub level: EntryButtonLevel,
+    pub tooltip: Option<String>,
 }
 
 pub struct EntryCategory {
@@ -85,8 +86,25 @@ fn icon_and_text<'a>(text: &'static str, icon_opt: Option<Icon>) -> Element<'a,
         .into()
 }
 
+fn with_tooltip<'a>(
+    content: Element<'a, ReSetMessage>,
+    tooltip_text: Option<String>,
+) -> Element<'a, ReSetMessage> {
+    match tooltip_text {
+        Some(tooltip_text) => tooltip(
+            content,
+            container(iced::widget::text(tooltip_text))
+                .padding(10)
+                .style(container::bordered_box),
+            tooltip::Position::Right,
+        )
+        .into(),
+        None => content,
+    }
+}
+
 fn create_button<'a>(entry: EntryButton) -> Element<'a, ReSetMessage> {
-    match entry.level {
+    let button = match entry.level {
         EntryButtonLevel::TopLevel => iced::widget::button(icon_and_text(entry.title, entry.icon))
             .on_press(entry.msg)
             .style(side_bar_button_style)
@@ -101,7 +119,8 @@ fn create_button<'a>(entry: EntryButton) -> Element<'a, ReSetMessage> {
                 .width(Length::Fill))
             .into()
         }
-    }
+    };
+    with_tooltip(button, entry.tooltip)
 }
 
 fn create_category<'a>(category: EntryCategory) -> Vec<Element<'a, ReSetMessage>> {
@@ -0,0 +1,33 @@
+use iced::{
+    alignment::Vertical,
+    widget::{container, row, text},
+    Element, Length,
+};
+use oxiced::widgets::oxi_button::{button, ButtonVariant};
+
+use crate::{utils::rounded_card, ReSetMessage};
+
+pub struct Toast {
+    /// Used to ignore dismiss timers of toasts that were already replaced
+    pub id: u32,
+    pub text: String,
+    pub action: Option<(&'static str, ReSetMessage)>,
+}
+
+pub fn toast_view(toast: &Toast, dismiss: ReSetMessage) -> Element<'_, ReSetMessage> {
+    let action = toast
+        .action
+        .clone()
+        .map(|(title, msg)| button(text(title), ButtonVariant::Primary).on_press(msg));
+    container(
+        row!(text(toast.text.clone()).width(Length::Fill))
+            .push_maybe(action)
+            .push(button(text("Dismiss"), ButtonVariant::RowEntry).on_press(dismiss))
+            .spacing(10)
+            .align_y(Vertical::Center),
+    )
+    .padding(10)
+    .width(Length::Fill)
+    .style(rounded_card)
+    .into()
+}
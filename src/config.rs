@@ -0,0 +1,86 @@
+use std::{collections::HashMap, fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+const CONFIG_FILE: &str = "ReSet-Iced.toml";
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub bluetooth: BluetoothConfig,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BluetoothConfig {
+    /// Connected devices below this battery percentage show a warning
+    pub battery_warning_threshold: u8,
+    /// Seconds a discovery runs before it is stopped automatically
+    pub scan_duration: u32,
+    /// Try again when connecting to a device fails
+    pub auto_reconnect: bool,
+    /// Automatic attempts before giving up
+    pub reconnect_attempts: u32,
+    /// Addresses of devices that are connected automatically, highest priority first
+    pub favorites: Vec<String>,
+    /// Offer to accept files that devices send over OBEX
+    pub receive_files: bool,
+    /// Folder accepted files are stored in
+    pub receive_folder: String,
+    /// Unix time each device address was last connected or disconnected
+    pub last_connected: HashMap<String, u64>,
+}
+
+impl Default for BluetoothConfig {
+    fn default() -> Self {
+        Self {
+            battery_warning_threshold: 20,
+            scan_duration: 10,
+            auto_reconnect: false,
+            reconnect_attempts: 3,
+            favorites: Vec::new(),
+            receive_files: false,
+            receive_folder: default_receive_folder(),
+            last_connected: HashMap::new(),
+        }
+    }
+}
+
+fn default_receive_folder() -> String {
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home)
+            .join("Downloads")
+            .to_string_lossy()
+            .to_string(),
+        None => String::new(),
+    }
+}
+
+fn config_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("reset"))
+}
+
+impl Config {
+    /// Missing or broken files fall back to the defaults
+    pub fn load() -> Self {
+        config_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(CONFIG_FILE)).ok())
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let dir = config_dir().ok_or(io::Error::new(
+            io::ErrorKind::NotFound,
+            "No config directory found",
+        ))?;
+        fs::create_dir_all(&dir)?;
+        let content = toml::to_string_pretty(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(dir.join(CONFIG_FILE), content)
+    }
+}
@@ -1,44 +1,53 @@
 use std::{
+    future::Future,
     ptr::null,
     sync::{
-        atomic::{AtomicBool, AtomicPtr, AtomicU8},
-        Arc,
+        atomic::AtomicPtr,
+        Arc, OnceLock,
     },
     time::Duration,
 };
 
 use audio::audio_impl::{watch_audio_dbus_signals, AudioModel, AudioMsg, AudioVariant};
 use bluetooth::bluetooth_impl::{watch_bluetooth_dbus_signals, BluetoothModel, BluetoothMsg};
+use clap::Parser;
+use cli::{Cli, Command};
 use components::{
     icons::Icon,
+    modal::modal,
+    reconnecting::{reconnect_failed, reconnecting},
     sidebar::{sidebar, EntryButton, EntryButtonLevel, EntryCategory},
 };
+use config::Config;
 use dbus_interface::ReSetDbusProxy;
 use iced::{
-    futures::{
-        channel::mpsc::{self, Sender},
-        executor::block_on,
-        SinkExt, Stream, StreamExt,
-    },
+    futures::{SinkExt, Stream, StreamExt},
+    keyboard::{self, Key, Modifiers},
     stream,
     widget::{column, row, scrollable, text},
     window::Settings,
     Element, Font, Size, Subscription, Task, Theme,
 };
 use network::network_impl::{NetworkModel, NetworkMsg};
+use page_model::{CreateResult, CreatedModel, PageModel};
 use re_set_lib::write_log_to_file;
 use re_set_lib::LOG;
 use reset_daemon::run_daemon;
+use utils::daemon_error_message;
 use zbus::Connection;
 
 mod audio;
 mod bluetooth;
+mod cli;
 mod components;
+mod config;
 mod dbus_interface;
 mod network;
+mod page_model;
+mod portal;
 mod utils;
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd, clap::ValueEnum)]
 enum PageId {
     // Chosen as it is probably the most useful page
     #[default]
@@ -47,16 +56,6 @@ enum PageId {
     Bluetooth,
 }
 
-impl Into<u8> for PageId {
-    fn into(self) -> u8 {
-        match self {
-            PageId::Audio => 0,
-            PageId::Network => 1,
-            PageId::Bluetooth => 2,
-        }
-    }
-}
-
 impl From<u8> for PageId {
     fn from(value: u8) -> Self {
         match value {
@@ -68,29 +67,63 @@ impl From<u8> for PageId {
 }
 
 impl PageId {
-    pub fn task(&self) -> Option<ReSetMessage> {
+    /// Sent to the model of the page once it is shown
+    pub fn enter(&self) -> ReSetMessage {
         match self {
-            PageId::Audio => None,
-            PageId::Network => None,
-            PageId::Bluetooth => Some(ReSetMessage::SubMsgBluetooth(
-                BluetoothMsg::StartBluetoothListener,
-            )),
+            PageId::Audio => ReSetMessage::SubMsgAudio(AudioMsg::EnterPage),
+            PageId::Network => ReSetMessage::SubMsgNetwork(NetworkMsg::EnterPage),
+            PageId::Bluetooth => ReSetMessage::SubMsgBluetooth(BluetoothMsg::EnterPage),
+        }
+    }
+
+    /// Sent to the model of the page once another page is shown
+    pub fn leave(&self) -> ReSetMessage {
+        match self {
+            PageId::Audio => ReSetMessage::SubMsgAudio(AudioMsg::LeavePage),
+            PageId::Network => ReSetMessage::SubMsgNetwork(NetworkMsg::LeavePage),
+            PageId::Bluetooth => ReSetMessage::SubMsgBluetooth(BluetoothMsg::LeavePage),
         }
     }
 }
 
-enum SenderOrNone {
-    None,
-    Sender(Sender<ReSetMessage>),
+/// Time the bundled daemon gets to start up
+const DAEMON_TIMEOUT: Duration = Duration::from_secs(5);
+/// Well known name of the daemon, watched to reconnect after it restarts
+const DAEMON_NAME: &str = "org.Xetibo.ReSet.Daemon";
+/// Wait before registering again when the daemon is not ready yet
+const REGISTER_RETRY: Duration = Duration::from_secs(2);
+/// Failed registrations before the overlay offers to retry or continue without the daemon
+const MAX_REGISTER_ATTEMPTS: u32 = 15;
+
+/// Runtime the bundled daemon was started on, None when another daemon was running
+static BUNDLED_DAEMON: OnceLock<tokio::runtime::Handle> = OnceLock::new();
+
+/// Starts the bundled daemon again after it crashed
+fn respawn_bundled_daemon() {
+    if let Some(runtime) = BUNDLED_DAEMON.get() {
+        LOG!("Restarting bundled daemon");
+        runtime.spawn(run_daemon(None));
+    }
 }
 
 struct ReSet {
-    sender: SenderOrNone,
-    ctx: Arc<Connection>,
+    /// None until the session bus could be reached
+    ctx: Option<Arc<Connection>>,
     current_page: PageId,
-    audio_model: AudioModel<'static>,
+    audio_model: PageModel<AudioModel>,
     network_model: NetworkModel,
-    bluetooth_model: BluetoothModel<'static>,
+    bluetooth_model: PageModel<BluetoothModel>,
+    /// False while the daemon is gone, shows the reconnecting overlay
+    daemon_available: bool,
+    /// True while a registration or the wait before the next one is pending
+    registering: bool,
+    register_attempts: u32,
+    /// The user chose to continue without the daemon
+    hide_reconnecting: bool,
+    /// Bumped on every registration so the watchers subscribe to the new daemon
+    daemon_generation: u32,
+    /// Runtime of main, the bundled daemon and the registration run on it
+    runtime: tokio::runtime::Handle,
 }
 
 #[derive(Debug, Clone)]
@@ -99,79 +132,221 @@ enum ReSetMessage {
     SubMsgNetwork(NetworkMsg),
     SubMsgBluetooth(BluetoothMsg),
     SetPage(PageId),
-    StartWorker(PageId, Arc<Connection>),
-    ReceiveSender(Sender<ReSetMessage>),
+    /// Creates the model of a page again after it failed
+    RetryPage(PageId),
+    /// A model was created in the background, along with the session bus connection
+    /// Daemon generation the model was created for, the connection and the model
+    AudioModelCreated(u32, Option<Arc<Connection>>, CreateResult<AudioModel>),
+    BluetoothModelCreated(u32, Option<Arc<Connection>>, CreateResult<BluetoothModel>),
+    /// The daemon name got or lost its owner
+    DaemonOwnerChanged(bool),
+    /// Registers with the daemon again
+    RegisterDaemon,
+    /// Result of registering with the restarted daemon
+    DaemonRegistered(bool),
+    /// Starts registering again after it was given up
+    RetryDaemon,
+    /// Hides the reconnecting overlay
+    DismissReconnecting,
+}
+
+fn audio_worker(ctx: Arc<Connection>) -> impl Stream<Item = ReSetMessage> {
+    stream::channel(100, move |mut output| async move {
+        // the page keeps working without live updates
+        if watch_audio_dbus_signals(&mut output, ctx).await.is_err() {
+            LOG!("Audio signal watcher stopped")
+        }
+    })
 }
 
-fn some_worker() -> impl Stream<Item = ReSetMessage> {
-    let mut page_id = PageId::Audio;
+fn bluetooth_worker(ctx: Arc<Connection>) -> impl Stream<Item = ReSetMessage> {
     stream::channel(100, move |mut output| async move {
-        let (sender, mut receiver) = mpsc::channel(100);
-        let current_page_id = Arc::new(AtomicU8::new(page_id.into()));
-        // TODO beforepr handle error
-        let _ = output.send(ReSetMessage::ReceiveSender(sender)).await;
-
-        println!("start");
-        loop {
-            // TODO resize event
-            println!("blet");
-            let input = receiver.select_next_some().await;
-            if let ReSetMessage::StartWorker(page_id, conn) = input {
-                current_page_id.store(page_id.into(), std::sync::atomic::Ordering::SeqCst);
-                match page_id {
-                    PageId::Audio => {
-                        watch_audio_dbus_signals(&mut output, conn, current_page_id.clone())
-                            .await
-                            .expect("audio watcher failed")
-                    } // TODO beforepr
-                    PageId::Network => (),
-                    PageId::Bluetooth => {
-                        watch_bluetooth_dbus_signals(&mut output, conn, current_page_id.clone())
-                            .await
-                            .expect("audio watcher failed")
-                    } // TODO beforepr
+        if watch_bluetooth_dbus_signals(&mut output, ctx).await.is_err() {
+            LOG!("Bluetooth signal watcher stopped")
+        }
+    })
+}
+
+/// Creates a page model without blocking the window, connects to the session bus if needed
+fn create_model<T, F>(
+    ctx: Option<Arc<Connection>>,
+    generation: u32,
+    create: impl FnOnce(Arc<Connection>) -> F + Send + 'static,
+    on_created: fn(u32, Option<Arc<Connection>>, CreateResult<T>) -> ReSetMessage,
+) -> Task<ReSetMessage>
+where
+    F: Future<Output = Result<T, zbus::Error>> + Send + 'static,
+    T: Send + 'static,
+{
+    Task::future(async move {
+        let ctx = match ctx {
+            Some(ctx) => ctx,
+            None => match Connection::session().await {
+                Ok(ctx) => Arc::new(ctx),
+                Err(error) => {
+                    return on_created(generation, None, Err(daemon_error_message(error)))
                 }
-                println!("what");
+            },
+        };
+        let model = create(ctx.clone())
+            .await
+            .map(CreatedModel::new)
+            .map_err(daemon_error_message);
+        on_created(generation, Some(ctx), model)
+    })
+}
+
+fn daemon_watcher() -> impl Stream<Item = ReSetMessage> {
+    stream::channel(10, |mut output| async move {
+        let watch = async {
+            let conn = Connection::session().await?;
+            let proxy = zbus::fdo::DBusProxy::new(&conn).await?;
+            let mut changes = proxy
+                .receive_name_owner_changed_with_args(&[(0, DAEMON_NAME)])
+                .await?;
+            while let Some(change) = changes.next().await {
+                let available = change.args()?.new_owner().is_some();
+                let _ = output
+                    .send(ReSetMessage::DaemonOwnerChanged(available))
+                    .await;
             }
+            Ok::<(), zbus::Error>(())
+        };
+        if watch.await.is_err() {
+            LOG!("Daemon watcher stopped")
         }
     })
 }
 
+fn audio_shortcuts(key: Key, modifiers: Modifiers) -> Option<ReSetMessage> {
+    match key {
+        Key::Character(character) if modifiers.command() => {
+            match (character.to_lowercase().as_str(), modifiers.shift()) {
+                ("z", false) => Some(ReSetMessage::SubMsgAudio(AudioMsg::Undo)),
+                ("z", true) | ("y", false) => Some(ReSetMessage::SubMsgAudio(AudioMsg::Redo)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 impl ReSet {
     fn subscription(&self) -> Subscription<ReSetMessage> {
-        Subscription::run(some_worker)
+        let shortcuts = match self.current_page {
+            PageId::Audio => keyboard::on_key_press(audio_shortcuts),
+            _ => Subscription::none(),
+        };
+        let mut subscriptions = vec![Subscription::run(daemon_watcher), shortcuts];
+        // each watcher runs while its model exists, independent of the shown page
+        if let Some(ctx) = self.ctx.as_ref().filter(|_| self.daemon_available) {
+            if self.audio_model.is_ready() {
+                subscriptions.push(Subscription::run_with_id(
+                    ("audio", self.daemon_generation),
+                    audio_worker(ctx.clone()),
+                ));
+            }
+            if self.bluetooth_model.is_ready() {
+                subscriptions.push(Subscription::run_with_id(
+                    ("bluetooth", self.daemon_generation),
+                    bluetooth_worker(ctx.clone()),
+                ));
+            }
+            // TODO network has no signal watcher yet, add it here once it does
+        }
+        Subscription::batch(subscriptions)
     }
 
     fn theme(&self) -> Theme {
         oxiced::theme::get_theme()
     }
 
-    fn new() -> (Self, Task<ReSetMessage>) {
-        // TODO beforepr handle error
-        let ctx = Arc::new(block_on(Connection::session()).unwrap());
-        let audio_context = async || {
-            AudioModel::new(&ctx.clone())
-                .await
-                .expect("Failed to create audio")
-            // TODO beforepr expect
-        };
-        let bluetooth_context = async || {
-            BluetoothModel::new(&ctx.clone())
-                .await
-                .expect("Failed to create audio")
-            // TODO beforepr expect
+    fn new(page_id: PageId, runtime: tokio::runtime::Handle) -> (Self, Task<ReSetMessage>) {
+        let reset = Self {
+            ctx: None,
+            current_page: page_id,
+            audio_model: PageModel::Uninitialized,
+            network_model: Default::default(),
+            bluetooth_model: PageModel::Uninitialized,
+            // the reconnecting overlay is shown until the daemon is registered,
+            // the page is created afterwards and the other ones once they are shown
+            daemon_available: false,
+            registering: true,
+            register_attempts: 0,
+            hide_reconnecting: false,
+            daemon_generation: 0,
+            runtime,
         };
-        (
-            Self {
-                sender: SenderOrNone::None,
-                ctx: ctx.clone(),
-                current_page: Default::default(),
-                audio_model: block_on(audio_context()),
-                network_model: Default::default(),
-                bluetooth_model: block_on(bluetooth_context()),
-            },
-            Task::none(),
-        )
+        (reset, Task::done(ReSetMessage::RegisterDaemon))
+    }
+
+    /// Creates the model of the page in the background unless it exists or is being created
+    fn init_page(&mut self, page_id: PageId) -> Task<ReSetMessage> {
+        let ctx = self.ctx.clone();
+        match page_id {
+            PageId::Audio if self.audio_model.needs_init() => {
+                self.audio_model = PageModel::Loading;
+                create_model(
+                    ctx,
+                    self.daemon_generation,
+                    |ctx| async move { AudioModel::new(&ctx).await },
+                    ReSetMessage::AudioModelCreated,
+                )
+            }
+            PageId::Bluetooth if self.bluetooth_model.needs_init() => {
+                self.bluetooth_model = PageModel::Loading;
+                let config = Config::load().bluetooth;
+                create_model(
+                    ctx,
+                    self.daemon_generation,
+                    |ctx| async move { BluetoothModel::new(&ctx, config).await },
+                    ReSetMessage::BluetoothModelCreated,
+                )
+            }
+            _ => Task::none(),
+        }
+    }
+
+    /// Registers with the daemon unless that is already in progress
+    fn start_registering(&mut self) -> Task<ReSetMessage> {
+        if self.registering {
+            return Task::none();
+        }
+        self.registering = true;
+        Task::done(ReSetMessage::RegisterDaemon)
+    }
+
+    /// Keeps the connection of a created model and enters its page if it is still shown
+    fn page_created(
+        &mut self,
+        page_id: PageId,
+        ctx: Option<Arc<Connection>>,
+    ) -> Task<ReSetMessage> {
+        if self.ctx.is_none() {
+            self.ctx = ctx;
+        }
+        if page_id == self.current_page {
+            self.start_page(page_id)
+        } else {
+            Task::none()
+        }
+    }
+
+    fn page_ready(&self, page_id: PageId) -> bool {
+        match page_id {
+            PageId::Audio => self.audio_model.is_ready(),
+            PageId::Network => true,
+            PageId::Bluetooth => self.bluetooth_model.is_ready(),
+        }
+    }
+
+    /// Enters the page, its signals are watched by the subscription
+    fn start_page(&self, page_id: PageId) -> Task<ReSetMessage> {
+        if self.page_ready(page_id) {
+            Task::done(page_id.enter())
+        } else {
+            Task::none()
+        }
     }
 
     fn title(&self) -> String {
@@ -181,9 +356,11 @@ impl ReSet {
     fn update(&mut self, message: ReSetMessage) -> Task<ReSetMessage> {
         match message {
             ReSetMessage::SubMsgAudio(audio_msg) => {
-                let update_fn = async || self.audio_model.update(audio_msg).await;
-                let output = block_on(update_fn());
-                if let Some(task) = output {
+                let task = self
+                    .audio_model
+                    .ready_mut()
+                    .and_then(|model| model.update(audio_msg));
+                if let Some(task) = task {
                     task
                 } else {
                     Task::none()
@@ -194,9 +371,11 @@ impl ReSet {
                 Task::none()
             }
             ReSetMessage::SubMsgBluetooth(bluetooth_msg) => {
-                let update_fn = async || self.bluetooth_model.update(bluetooth_msg).await;
-                let output = block_on(update_fn());
-                if let Some(task) = output.ok() {
+                let output = self
+                    .bluetooth_model
+                    .ready_mut()
+                    .map(|model| model.update(bluetooth_msg));
+                if let Some(Ok(task)) = output {
                     task
                 } else {
                     Task::none()
@@ -206,34 +385,83 @@ impl ReSet {
                 if page_id == self.current_page {
                     Task::none()
                 } else {
+                    let previous_page = self.current_page;
                     self.current_page = page_id;
                     Task::batch([
-                        if let Some(msg) = PageId::task(&page_id) {
-                            Task::done(msg)
-                        } else {
-                            Task::none()
-                        },
-                        Task::done(ReSetMessage::StartWorker(page_id, self.ctx.clone())),
+                        Task::done(previous_page.leave()),
+                        self.init_page(page_id),
+                        self.start_page(page_id),
                     ])
                 }
             }
-            ReSetMessage::StartWorker(page_id, connection) => {
-                match &mut self.sender {
-                    SenderOrNone::None => (),
-                    SenderOrNone::Sender(sender) => {
-                        let fun = async || {
-                            sender
-                                .send(ReSetMessage::StartWorker(page_id, connection))
-                                .await
-                        };
-                        let _ = block_on(fun());
-                    }
-                };
-                Task::none()
+            ReSetMessage::DaemonOwnerChanged(false) => {
+                self.daemon_available = false;
+                self.hide_reconnecting = false;
+                self.register_attempts = 0;
+                // nobody else would ever own the name again
+                respawn_bundled_daemon();
+                self.start_registering()
             }
-            ReSetMessage::ReceiveSender(sender) => {
-                self.sender = SenderOrNone::Sender(sender);
-                Task::none()
+            ReSetMessage::DaemonOwnerChanged(true) => {
+                // the bundled daemon appearing after it was registered with
+                if self.daemon_available {
+                    return Task::none();
+                }
+                self.register_attempts = 0;
+                self.start_registering()
+            }
+            ReSetMessage::RegisterDaemon => {
+                let ctx = self.ctx.clone();
+                let runtime = self.runtime.clone();
+                Task::perform(
+                    async move { runtime.spawn(register_client(ctx)).await },
+                    |result| ReSetMessage::DaemonRegistered(matches!(result, Ok(Ok(())))),
+                )
+            }
+            ReSetMessage::DaemonRegistered(false) => {
+                self.register_attempts += 1;
+                if self.register_attempts >= MAX_REGISTER_ATTEMPTS {
+                    self.registering = false;
+                    return Task::none();
+                }
+                Task::perform(async_io::Timer::after(REGISTER_RETRY), |_| {
+                    ReSetMessage::RegisterDaemon
+                })
+            }
+            ReSetMessage::RetryDaemon => {
+                self.register_attempts = 0;
+                respawn_bundled_daemon();
+                self.start_registering()
+            }
+            ReSetMessage::DismissReconnecting => {
+                self.hide_reconnecting = true;
+                // the page shows the daemon error with a retry button
+                self.init_page(self.current_page)
+            }
+            ReSetMessage::DaemonRegistered(true) => {
+                // the proxies of the old models point to the previous daemon
+                self.registering = false;
+                self.daemon_available = true;
+                self.daemon_generation += 1;
+                self.audio_model = PageModel::Uninitialized;
+                self.bluetooth_model = PageModel::Uninitialized;
+                self.init_page(self.current_page)
+            }
+            ReSetMessage::RetryPage(page_id) => self.init_page(page_id),
+            ReSetMessage::AudioModelCreated(generation, ctx, result) => {
+                // created for a daemon that was restarted since, a new one is on its way
+                if generation != self.daemon_generation {
+                    return Task::none();
+                }
+                self.audio_model = result.into();
+                self.page_created(PageId::Audio, ctx)
+            }
+            ReSetMessage::BluetoothModelCreated(generation, ctx, result) => {
+                if generation != self.daemon_generation {
+                    return Task::none();
+                }
+                self.bluetooth_model = result.into();
+                self.page_created(PageId::Bluetooth, ctx)
             }
         }
     }
@@ -246,18 +474,21 @@ impl ReSet {
                     icon: Some(Icon::Mic),
                     msg: ReSetMessage::SubMsgAudio(AudioMsg::SetAudioVariant(AudioVariant::Input)),
                     level: EntryButtonLevel::SubLevel,
+                    tooltip: None,
                 },
                 EntryButton {
                     title: "Output",
                     icon: Some(Icon::Volume),
                     msg: ReSetMessage::SubMsgAudio(AudioMsg::SetAudioVariant(AudioVariant::Output)),
                     level: EntryButtonLevel::SubLevel,
+                    tooltip: None,
                 },
                 EntryButton {
                     title: "Cards",
                     icon: Some(Icon::AudioCards),
                     msg: ReSetMessage::SubMsgAudio(AudioMsg::SetAudioVariant(AudioVariant::Cards)),
                     level: EntryButtonLevel::SubLevel,
+                    tooltip: None,
                 },
                 EntryButton {
                     title: "Devices",
@@ -266,6 +497,16 @@ impl ReSet {
                         AudioVariant::Devices,
                     )),
                     level: EntryButtonLevel::SubLevel,
+                    tooltip: None,
+                },
+                EntryButton {
+                    title: "Equalizer",
+                    icon: Some(Icon::Equalizer),
+                    msg: ReSetMessage::SubMsgAudio(AudioMsg::SetAudioVariant(
+                        AudioVariant::Equalizer,
+                    )),
+                    level: EntryButtonLevel::SubLevel,
+                    tooltip: None,
                 },
             ];
             let base_audio = EntryButton {
@@ -275,6 +516,7 @@ impl ReSet {
                     AudioVariant::InputAndOutput,
                 )),
                 level: EntryButtonLevel::TopLevel,
+                tooltip: None,
             };
             let audio = EntryCategory {
                 main_entry: base_audio,
@@ -286,6 +528,7 @@ impl ReSet {
                     icon: Some(Icon::Wifi),
                     msg: ReSetMessage::SetPage(PageId::Network),
                     level: EntryButtonLevel::TopLevel,
+                    tooltip: None,
                 },
                 sub_entries: Vec::new(),
             };
@@ -295,26 +538,57 @@ impl ReSet {
                     icon: Some(Icon::Bluetooth),
                     msg: ReSetMessage::SetPage(PageId::Bluetooth),
                     level: EntryButtonLevel::TopLevel,
+                    tooltip: self
+                        .bluetooth_model
+                        .ready()
+                        .and_then(|model| model.battery_summary()),
                 },
                 sub_entries: Vec::new(),
             };
             vec![audio, network, bluetooth]
         };
-        row!(
+        let base = row!(
             // TODO beforepr set audio and network
             sidebar(entries),
             // TODO beforepr make a wrapper over everything ->
             // 3 views  -> 1 box without sidebar -> 1 box with sidebar -> 2 boxes with sidebar
             scrollable(match self.current_page {
-                PageId::Audio => self
-                    .audio_model
-                    .view()
-                    .unwrap_or(column!(text("le error has happened")).into()),
+                PageId::Audio => self.audio_model.view(
+                    "Audio",
+                    ReSetMessage::RetryPage(PageId::Audio),
+                    |model| model
+                        .view()
+                        .unwrap_or(column!(text("le error has happened")).into())
+                ),
                 PageId::Network => self.network_model.view(),
-                PageId::Bluetooth => self.bluetooth_model.view(),
+                PageId::Bluetooth => self.bluetooth_model.view(
+                    "Bluetooth",
+                    ReSetMessage::RetryPage(PageId::Bluetooth),
+                    |model| model.view()
+                ),
             }),
-        )
-        .into()
+        );
+        if !self.daemon_available && !self.hide_reconnecting {
+            if self.registering {
+                modal(base, reconnecting())
+            } else {
+                modal(
+                    base,
+                    reconnect_failed(
+                        ReSetMessage::RetryDaemon,
+                        ReSetMessage::DismissReconnecting,
+                    ),
+                )
+            }
+        } else if let Some(dialog) = self
+            .bluetooth_model
+            .ready()
+            .and_then(|model| model.dialog())
+        {
+            modal(base, dialog)
+        } else {
+            base.into()
+        }
     }
 
     //fn scale_factor(&self) -> f64 {
@@ -322,32 +596,53 @@ impl ReSet {
     //}
 }
 
+/// Registers with the running daemon, or starts the bundled one if there is none.
+/// Must run on the tokio runtime, the bundled daemon is spawned on it.
+async fn register_client(ctx: Option<Arc<Connection>>) -> Result<(), zbus::Error> {
+    let conn = match ctx {
+        Some(ctx) => ctx,
+        None => Arc::new(Connection::session().await?),
+    };
+    let reset_proxy = ReSetDbusProxy::new(&conn).await?;
+    let result = reset_proxy.register_client("ReSet-Iced").await.map(|_| ());
+    // a bundled daemon that is still starting is registered with on the next attempt
+    if result.is_ok() || BUNDLED_DAEMON.set(tokio::runtime::Handle::current()).is_err() {
+        return result;
+    }
+
+    // Start daemon and retry once it owns its name
+    let dbus = zbus::fdo::DBusProxy::new(&conn).await?;
+    let mut owner_changes = dbus
+        .receive_name_owner_changed_with_args(&[(0, DAEMON_NAME)])
+        .await?;
+    tokio::task::spawn(run_daemon(None));
+    if tokio::time::timeout(DAEMON_TIMEOUT, owner_changes.next())
+        .await
+        .is_err()
+    {
+        return Err(zbus::Error::Failure("The bundled daemon did not start".to_string()));
+    }
+    reset_proxy.register_client("ReSet-Iced").await?;
+    LOG!("Using Bundled Daemon");
+    Ok(())
+}
+
 #[tokio::main]
 pub async fn main() -> Result<(), iced::Error> {
-    let conn = Connection::session().await.unwrap();
-    let reset_proxy = ReSetDbusProxy::new(&conn).await.unwrap();
-
-    let res = reset_proxy.register_client("ReSet-Iced").await;
-
-    if res.is_err() {
-        // Start daemon and retry
-        let ready = Arc::new(AtomicBool::new(false));
-        let start = std::time::SystemTime::now();
-        tokio::task::spawn(run_daemon(Some(ready.clone())));
-        while !ready.load(std::sync::atomic::Ordering::SeqCst) {
-            if start.elapsed().unwrap_or(Duration::from_secs(1)) >= Duration::from_secs(1) {
-                return Err(iced::Error::WindowCreationFailed(Box::from(
-                    "Failed to get daemon",
-                )));
+    let cli = Cli::parse();
+    let page_id = match cli.command {
+        Some(Command::Page { page }) => page,
+        Some(command) => {
+            // commands print the error of the daemon call
+            if register_client(None).await.is_err() {
+                LOG!("Failed to get daemon")
             }
+            std::process::exit(cli::run(command, cli.json).await)
         }
-        // Second try without any catch, this means there was no way to connect to the daemon
-        reset_proxy
-            .register_client("ReSet-Iced")
-            .await
-            .expect("Failed to get daemon");
-        LOG!("Using Bundled Daemon")
-    }
+        None => PageId::default(),
+    };
+    // the window registers with the daemon once it is open
+    let runtime = tokio::runtime::Handle::current();
 
     let icon = iced::window::icon::from_file("./assets/ReSet.png"); //.ok();
     let icon = if let Ok(icon) = icon {
@@ -382,5 +677,5 @@ pub async fn main() -> Result<(), iced::Error> {
         .theme(ReSet::theme)
         .default_font(Font::with_name("Adwaita Sans"))
         .subscription(ReSet::subscription)
-        .run_with(ReSet::new)
+        .run_with(move || ReSet::new(page_id, runtime))
 }
@@ -19,6 +19,8 @@ pub struct NetworkModel {
 #[derive(Debug, Clone)]
 pub enum NetworkMsg {
     SubMsgWireless(WirelessMsg),
+    EnterPage,
+    LeavePage,
 }
 
 impl NetworkModel {
@@ -29,6 +31,7 @@ impl NetworkModel {
     pub fn update(&mut self, msg: NetworkMsg) {
         match msg {
             NetworkMsg::SubMsgWireless(wireless_msg) => self.wireless_model.update(wireless_msg),
+            NetworkMsg::EnterPage | NetworkMsg::LeavePage => (),
         }
     }
 
@@ -0,0 +1,100 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use iced::Element;
+
+use crate::{
+    components::{page_error::page_error, page_loading::page_loading},
+    ReSetMessage,
+};
+
+/// A page model that is created when the page is first shown and may fail to be created
+#[derive(Default)]
+pub enum PageModel<T> {
+    #[default]
+    Uninitialized,
+    /// Created in the background, the page shows a loading state meanwhile
+    Loading,
+    Failed(String),
+    Ready(T),
+}
+
+impl<T> PageModel<T> {
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Self::Ready(_))
+    }
+
+    /// Neither created nor being created
+    pub fn needs_init(&self) -> bool {
+        matches!(self, Self::Uninitialized | Self::Failed(_))
+    }
+
+    pub fn ready(&self) -> Option<&T> {
+        match self {
+            Self::Ready(model) => Some(model),
+            _ => None,
+        }
+    }
+
+    pub fn ready_mut(&mut self) -> Option<&mut T> {
+        match self {
+            Self::Ready(model) => Some(model),
+            _ => None,
+        }
+    }
+
+    /// Shows the model, or the reason it is missing along with a retry button
+    pub fn view<'a>(
+        &'a self,
+        title: &'a str,
+        retry: ReSetMessage,
+        ready: impl FnOnce(&'a T) -> Element<'a, ReSetMessage>,
+    ) -> Element<'a, ReSetMessage> {
+        match self {
+            Self::Ready(model) => ready(model),
+            Self::Failed(message) => page_error(title, message, retry),
+            Self::Uninitialized | Self::Loading => page_loading(title),
+        }
+    }
+}
+
+/// Hands a model created in a task over to the update loop, messages need to be Clone
+pub struct CreatedModel<T>(Arc<Mutex<Option<T>>>);
+
+impl<T> CreatedModel<T> {
+    pub fn new(model: T) -> Self {
+        Self(Arc::new(Mutex::new(Some(model))))
+    }
+
+    /// The model can only be taken once
+    pub fn take(&self) -> Option<T> {
+        self.0.lock().ok()?.take()
+    }
+}
+
+/// Result of creating a model, the error is shown on the page
+pub type CreateResult<T> = Result<CreatedModel<T>, String>;
+
+impl<T> Clone for CreatedModel<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Debug for CreatedModel<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CreatedModel")
+    }
+}
+
+impl<T> From<CreateResult<T>> for PageModel<T> {
+    fn from(result: CreateResult<T>) -> Self {
+        match result.map(|model| model.take()) {
+            Ok(Some(model)) => Self::Ready(model),
+            Ok(None) => Self::Uninitialized,
+            Err(message) => Self::Failed(message),
+        }
+    }
+}
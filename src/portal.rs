@@ -0,0 +1,105 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use iced::futures::StreamExt;
+use zbus::{
+    proxy,
+    zvariant::{OwnedObjectPath, OwnedValue, Value},
+    Connection,
+};
+
+#[proxy(
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop",
+    interface = "org.freedesktop.portal.FileChooser"
+)]
+trait FileChooser {
+    fn open_file(
+        &self,
+        parent_window: &str,
+        title: &str,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<OwnedObjectPath>;
+}
+
+#[proxy(
+    default_service = "org.freedesktop.portal.Desktop",
+    interface = "org.freedesktop.portal.Request"
+)]
+trait Request {
+    #[zbus(signal)]
+    fn response(&self, response: u32, results: HashMap<String, OwnedValue>) -> zbus::Result<()>;
+}
+
+/// Makes the request handles of concurrent dialogs unique
+static REQUEST_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// Decodes a file:// URI returned by the portal
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut index = 0;
+    while index < encoded.len() {
+        if encoded[index] == b'%' && index + 2 < encoded.len() {
+            let hex = std::str::from_utf8(&encoded[index + 1..index + 3]).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(encoded[index]);
+            index += 1;
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(decoded).ok()?))
+}
+
+/// Opens the file chooser of the desktop portal, None if the dialog was canceled
+pub async fn choose_file(
+    conn: &Connection,
+    title: &str,
+    directory: bool,
+) -> Result<Option<PathBuf>, zbus::Error> {
+    let token = format!(
+        "reset_{}_{}",
+        std::process::id(),
+        REQUEST_COUNT.fetch_add(1, Ordering::SeqCst)
+    );
+    let sender = match conn.unique_name() {
+        Some(name) => name.trim_start_matches(':').replace('.', "_"),
+        None => return Err(zbus::Error::Failure("No unique name".to_string())),
+    };
+    // subscribe before the call, otherwise a fast response could be missed
+    let request = RequestProxy::builder(conn)
+        .path(format!(
+            "/org/freedesktop/portal/desktop/request/{}/{}",
+            sender, token
+        ))?
+        .build()
+        .await?;
+    let mut responses = request.receive_response().await?;
+
+    let mut options = HashMap::new();
+    options.insert("handle_token", Value::from(token.as_str()));
+    options.insert("directory", Value::from(directory));
+    FileChooserProxy::new(conn)
+        .await?
+        .open_file("", title, options)
+        .await?;
+
+    let response = match responses.next().await {
+        Some(response) => response,
+        None => return Ok(None),
+    };
+    let args = response.args()?;
+    // 1 is a canceled dialog, 2 any other failure
+    if args.response != 0 {
+        return Ok(None);
+    }
+    let uris = match args.results.get("uris") {
+        Some(uris) => Vec::<String>::try_from(uris.try_clone()?)?,
+        None => Vec::new(),
+    };
+    Ok(uris.first().and_then(|uri| uri_to_path(uri)))
+}
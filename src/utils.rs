@@ -26,3 +26,12 @@ impl<T> TToError<T> for Option<T> {
         }
     }
 }
+
+/// Extracts the message the daemon sent along with an error, falls back to the zbus description
+pub fn daemon_error_message(error: zbus::Error) -> String {
+    match error {
+        zbus::Error::MethodError(name, message, _) => message.unwrap_or_else(|| name.to_string()),
+        zbus::Error::Failure(message) => message,
+        error => error.to_string(),
+    }
+}